@@ -1,5 +1,5 @@
 use crate::setup::setup;
-use clap::{Parser, Subcommand};
+use clap::{Args, Parser, Subcommand, ValueEnum};
 
 #[derive(Debug, Parser)]
 #[clap(name = "wireman", version)]
@@ -9,16 +9,81 @@ struct App {
 }
 
 #[derive(Debug, Subcommand)]
-enum Command {
+pub enum Command {
     /// Runs a health check and prompts configuration details.
     Check,
+    /// Load tests a method with the request of a history save spot.
+    Bench(BenchArgs),
+    /// Serves all services of the protos with mock responses.
+    Mock(MockArgs),
 }
 
-pub fn parse() {
-    let app = App::parse();
-    match app.command {
-        Command::Check => {
-            let _ = setup(true);
-        }
-    }
+/// The arguments of the `bench` command.
+#[derive(Debug, Args)]
+pub struct BenchArgs {
+    /// The full name of the method, e.g. `helloworld.Greeter/SayHello`.
+    pub method: String,
+
+    /// The history save spot (1-5) of the request. The request template
+    /// and the default address are used if the spot is empty.
+    #[arg(short, long, default_value_t = 1, value_parser = clap::value_parser!(u8).range(1..=5))]
+    pub spot: u8,
+
+    /// The number of requests.
+    #[arg(short = 'n', long, default_value_t = 200)]
+    pub requests: u64,
+
+    /// Runs for a duration, e.g. `10s` or `1m`, instead of a number of
+    /// requests.
+    #[arg(short = 'z', long)]
+    pub duration: Option<String>,
+
+    /// The number of requests which are sent concurrently.
+    #[arg(short, long, default_value_t = 50)]
+    pub concurrency: usize,
+
+    /// The maximum number of requests per second.
+    #[arg(short, long)]
+    pub rate: Option<f64>,
+
+    /// The format of the report.
+    #[arg(short, long, value_enum, default_value_t = BenchFormat::Text)]
+    pub format: BenchFormat,
+}
+
+/// The format of the benchmark report.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum BenchFormat {
+    Text,
+    Json,
+}
+
+/// The arguments of the `mock` command.
+#[derive(Debug, Args)]
+pub struct MockArgs {
+    /// The address to listen on, e.g. `127.0.0.1:50051`. Defaults to
+    /// the host and port of the default address of the config.
+    #[arg(short, long)]
+    pub address: Option<String>,
+
+    /// A json file with a list of rules which answer requests. Can be
+    /// given multiple times, the first matching rule answers.
+    #[arg(short, long)]
+    pub rules: Vec<String>,
+
+    /// The history save spot (1-5) whose saved responses are served if
+    /// no rule matches.
+    #[arg(short, long, default_value_t = 1, value_parser = clap::value_parser!(u8).range(1..=5))]
+    pub spot: u8,
+}
+
+/// Parses the command line arguments.
+#[must_use]
+pub fn parse() -> Command {
+    App::parse().command
+}
+
+/// Runs a health check of the config and prompts its details.
+pub fn check() {
+    let _ = setup(true);
 }
@@ -7,10 +7,34 @@
 //!
 //! - `includes`: A list of include directories for `gRPC`.
 //! - `files`: A list of .proto files to include.
+//! - `descriptor_sets`: A list of binary encoded file descriptor sets to include.
 //! - `server`
 //!   - `default_address`: The default address of the `gRPC` server.
+//!   - `reflection`: Whether to load the protos via server reflection.
+//!   - `timeout_ms`: The default timeout of a call in milliseconds.
+//!   - `transport`: The protocol spoken with the servers, `grpc`, `grpc-web`,
+//!     `connect` or `connect-json`.
+//!   - `transports`: The protocol spoken with specific addresses.
+//!   - `compression`: Whether requests are sent (`send_gzip`) and responses
+//!     are accepted (`accept_gzip`) gzip compressed.
+//!   - `compressions`: The compression of the calls to specific addresses.
+//!   - `service_config`: A `gRPC` service config json with the timeouts and
+//!     retry policies of the methods.
+//!   - `proxy`: The HTTP CONNECT proxy of all connections. Defaults to the
+//!     `HTTPS_PROXY` and `HTTP_PROXY` environment variables, `NO_PROXY` is
+//!     honored in both cases.
+//!   - `connect_timeout_ms`, `keepalive_interval_ms`, `keepalive_timeout_ms`:
+//!     The connect timeout and the HTTP/2 keepalive in milliseconds.
+//!   - `initial_stream_window_size`, `initial_connection_window_size`: The
+//!     initial HTTP/2 window sizes in bytes.
+//!   - `tcp_nodelay`: Whether `TCP_NODELAY` is set on the sockets.
+//!   - `max_decoding_message_size`, `max_encoding_message_size`: The maximum
+//!     size of the response and request messages in bytes.
 //! - `history`
 //!   - `directory`: The folder path where the history should be kept
+//! - `tls`
+//!   - `custom_cert`: A custom CA certificate for `https` addresses.
+//!   - `client_cert`, `client_key`: The client certificate and key for mutual TLS.
 pub mod cli;
 pub mod config;
 pub mod error;
@@ -77,7 +77,7 @@ pub fn setup(dry_run: bool) -> Result<Config> {
         let history_dir = match history_dir_checked(config_dir_path, &config.history) {
             Err(err) => {
                 if dry_run {
-                    println!("{:<20} Error: {}", "History:", err.to_string());
+                    println!("{:<20} Error: {}", "History:", err);
                 }
                 return Err(Error::SetupError(err));
             }
@@ -179,9 +179,9 @@ fn history_dir_checked(
         history_dir_path = default_history_path.to_string();
     }
 
-    if Path::new(&history_dir_path)
+    if !Path::new(&history_dir_path)
         .parent()
-        .map_or(true, |p| !p.exists())
+        .is_some_and(Path::exists)
     {
         return Err(SetupError::new(format!(
             "Non existant parent of history directory {history_dir_path}.",
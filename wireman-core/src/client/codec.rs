@@ -1,5 +1,6 @@
 //! This file is taken from `https://github.com/andrewhickman/grpc-client`
 use crate::descriptor::{DynamicMessage, RequestMessage, ResponseMessage};
+use bytes::{Buf, BufMut};
 use prost_reflect::prost::Message;
 use prost_reflect::MethodDescriptor;
 use tonic::{
@@ -8,7 +9,8 @@ use tonic::{
 };
 
 /// `DynamicCodec` is a customizable `gRPC` codec that can handle requests and responses with
-/// dynamic message descriptors.
+/// dynamic message descriptors. Requests with a raw payload are passed through verbatim, the
+/// responses keep their raw bytes next to the decoded message.
 #[derive(Debug, Clone)]
 pub struct DynamicCodec(MethodDescriptor);
 
@@ -45,6 +47,12 @@ impl Encoder for DynamicCodec {
         // Ensure the request message descriptor matches the codec's input descriptor.
         debug_assert_eq!(request.message_descriptor(), self.0.input());
 
+        // Pass a raw payload through verbatim.
+        if let Some(raw) = request.raw() {
+            dst.put_slice(raw);
+            return Ok(());
+        }
+
         // Encode the request message into the destination buffer.
         request
             .message()
@@ -65,14 +73,80 @@ impl Decoder for DynamicCodec {
         let mut message = DynamicMessage::new(self.0.output());
 
         // Merge the source buffer into the dynamic message.
+        let raw = src.copy_to_bytes(src.remaining());
         message
-            .merge(src)
+            .merge(raw.clone())
             .map_err(|err| Status::internal(err.to_string()))?;
 
         // Create a response message and set its message to the decoded dynamic message.
         let mut response = ResponseMessage::new(self.0.output(), self.0.clone());
         response.set_message(message);
+        response.set_raw(raw);
 
         Ok(Some(response))
     }
 }
+
+/// `ServerCodec` is the reverse of [`DynamicCodec`]. It decodes the requests and encodes
+/// the responses of a method, so that the method can be served.
+#[derive(Debug, Clone)]
+pub struct ServerCodec(MethodDescriptor);
+
+impl ServerCodec {
+    /// Create a new `ServerCodec` with the provided [`MethodDescriptor`].
+    #[must_use]
+    pub fn new(desc: MethodDescriptor) -> Self {
+        ServerCodec(desc)
+    }
+}
+
+impl Codec for ServerCodec {
+    type Encode = ResponseMessage;
+    type Decode = RequestMessage;
+
+    type Encoder = ServerCodec;
+    type Decoder = ServerCodec;
+
+    fn encoder(&mut self) -> Self::Encoder {
+        self.clone()
+    }
+
+    fn decoder(&mut self) -> Self::Decoder {
+        self.clone()
+    }
+}
+
+impl Encoder for ServerCodec {
+    type Item = ResponseMessage;
+    type Error = Status;
+
+    // Encodes a response message and writes it to the destination buffer.
+    fn encode(&mut self, response: Self::Item, dst: &mut EncodeBuf<'_>) -> Result<(), Self::Error> {
+        debug_assert_eq!(response.message.descriptor(), self.0.output());
+        response
+            .message
+            .encode(dst)
+            .map_err(|err| Status::internal(err.to_string()))?;
+
+        Ok(())
+    }
+}
+
+impl Decoder for ServerCodec {
+    type Item = RequestMessage;
+    type Error = Status;
+
+    /// Decodes a request message from the source buffer.
+    fn decode(&mut self, src: &mut DecodeBuf<'_>) -> Result<Option<Self::Item>, Self::Error> {
+        let mut message = DynamicMessage::new(self.0.input());
+        let raw = src.copy_to_bytes(src.remaining());
+        message
+            .merge(raw)
+            .map_err(|err| Status::invalid_argument(err.to_string()))?;
+
+        let mut request = RequestMessage::new(self.0.input(), self.0.clone());
+        request.set_message(message);
+
+        Ok(Some(request))
+    }
+}
@@ -0,0 +1,27 @@
+//! The compression of the messages of a call.
+use tonic::client::Grpc;
+use tonic::codec::CompressionEncoding;
+
+/// Whether the messages of a call are gzip compressed. Applies to
+/// `gRPC` and `gRPC-Web`, the Connect protocol sends and accepts
+/// only uncompressed messages.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct Compression {
+    /// Whether the requests are sent gzip compressed.
+    pub send_gzip: bool,
+    /// Whether the server may send gzip compressed responses.
+    pub accept_gzip: bool,
+}
+
+impl Compression {
+    /// Applies the compression to a `gRPC` client.
+    pub(super) fn apply<T>(self, mut grpc: Grpc<T>) -> Grpc<T> {
+        if self.send_gzip {
+            grpc = grpc.send_compressed(CompressionEncoding::Gzip);
+        }
+        if self.accept_gzip {
+            grpc = grpc.accept_compressed(CompressionEncoding::Gzip);
+        }
+        grpc
+    }
+}
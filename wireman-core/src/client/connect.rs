@@ -0,0 +1,577 @@
+//! A client for the Connect protocol. Unary calls are sent as plain
+//! `POST` requests with the encoded message as body. Streaming calls
+//! wrap each message in the Connect envelope and end with a json
+//! message which holds the trailers and the error, if any.
+use super::connection::ConnectionConfig;
+use super::metrics::ConnectTime;
+use super::transport::{host_header, http1_client, set_host, Http1Client};
+use crate::descriptor::metadata::Metadata;
+use crate::descriptor::metadata::BASE64;
+use crate::descriptor::status::{decode_any, status_json};
+use crate::descriptor::{DynamicMessage, RequestMessage, ResponseMessage};
+use crate::error::{code_from_str, Error, GrpcStatus};
+use crate::Result;
+use base64::Engine;
+use bytes::{Buf, Bytes, BytesMut};
+use http::header::{HeaderName, CONTENT_TYPE};
+use http::{HeaderMap, HeaderValue, Method, Request, Response, StatusCode, Uri};
+use http_body::Body;
+use prost::Message;
+use prost_reflect::{DescriptorPool, MethodDescriptor};
+use prost_types::Any;
+use serde::Deserialize;
+use serde_json::json;
+use std::collections::HashMap;
+use tonic::metadata::MetadataMap;
+use tonic::Code;
+use tower::{Service, ServiceExt};
+
+/// The version of the Connect protocol.
+const PROTOCOL_VERSION: &str = "1";
+
+/// The header of the protocol version.
+const PROTOCOL_VERSION_HEADER: &str = "connect-protocol-version";
+
+/// The header of the timeout of a call in milliseconds.
+const TIMEOUT_HEADER: &str = "connect-timeout-ms";
+
+/// The prefix of the trailers of a unary response.
+const TRAILER_PREFIX: &str = "trailer-";
+
+/// The flag of an envelope which holds a compressed message.
+const COMPRESSED_FLAG: u8 = 0b01;
+
+/// The flag of an envelope which ends the stream.
+const END_STREAM_FLAG: u8 = 0b10;
+
+/// The length of the envelope prefix: one byte of flags and
+/// the big-endian length of the message.
+const ENVELOPE_PREFIX_LEN: usize = 5;
+
+/// The encoding of the messages.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum Codec {
+    /// Binary protobuf, `application/proto`.
+    Proto,
+    /// Protobuf json, `application/json`.
+    Json,
+}
+
+impl Codec {
+    fn unary_content_type(self) -> &'static str {
+        match self {
+            Self::Proto => "application/proto",
+            Self::Json => "application/json",
+        }
+    }
+
+    fn streaming_content_type(self) -> &'static str {
+        match self {
+            Self::Proto => "application/connect+proto",
+            Self::Json => "application/connect+json",
+        }
+    }
+
+    /// Encodes the message of a request. A raw payload is passed
+    /// through verbatim, which requires the binary encoding.
+    fn encode(self, req: &RequestMessage) -> Result<Vec<u8>> {
+        match (self, req.raw()) {
+            (Self::Proto, Some(raw)) => Ok(raw.to_vec()),
+            (Self::Proto, None) => Ok(req.message().encode_to_vec()),
+            (Self::Json, Some(_)) => Err(Error::Internal(String::from(
+                "raw payloads are not supported over Connect (JSON)",
+            ))),
+            (Self::Json, None) => Ok(req.message().to_json()?.into_bytes()),
+        }
+    }
+
+    fn decode(self, method: &MethodDescriptor, bytes: &[u8]) -> Result<ResponseMessage> {
+        let mut message = DynamicMessage::new(method.output());
+        match self {
+            Self::Proto => message
+                .merge(bytes)
+                .map_err(|err| Error::ConnectProtocolError(err.to_string()))?,
+            Self::Json => {
+                let json = std::str::from_utf8(bytes)
+                    .map_err(|err| Error::ConnectProtocolError(err.to_string()))?;
+                message.from_json(json)?;
+            }
+        }
+        let mut response = ResponseMessage::new(method.output(), method.clone());
+        response.set_message(message);
+        if self == Self::Proto {
+            response.set_raw(Bytes::copy_from_slice(bytes));
+        }
+        Ok(response)
+    }
+}
+
+/// A client which speaks the Connect protocol over HTTP/1.1.
+#[derive(Clone)]
+pub(super) struct ConnectClient {
+    client: Http1Client<hyper::Body>,
+    origin: Uri,
+    codec: Codec,
+    host: Option<HeaderValue>,
+    /// The maximum size of a message of a response stream.
+    max_message_size: usize,
+}
+
+impl ConnectClient {
+    /// Returns a new Connect client. `https` uris are connected via TLS.
+    /// The duration of new connections is recorded in `connect_time`.
+    ///
+    /// # Errors
+    /// - Failed to load the tls certificates or the client key
+    /// - The authority override is invalid
+    pub(super) fn new(
+        origin: Uri,
+        connection: &ConnectionConfig,
+        codec: Codec,
+        connect_time: &ConnectTime,
+    ) -> Result<Self> {
+        Ok(Self {
+            client: http1_client(&origin, connection, connect_time)?,
+            host: host_header(connection.tls())?,
+            origin,
+            codec,
+            max_message_size: connection.endpoint().max_decoding_message_size(),
+        })
+    }
+
+    /// Makes a unary call.
+    ///
+    /// # Errors
+    /// - Failed to encode the request or decode the response
+    /// - Server call failed
+    pub(super) async fn unary(&mut self, req: &RequestMessage) -> Result<ResponseMessage> {
+        let method = req.method_descriptor();
+        let body = self.codec.encode(req)?;
+        let request = self.request(req, self.codec.unary_content_type(), body)?;
+        let response = self.send(request).await?;
+        let (parts, body) = response.into_parts();
+        let body = hyper::body::to_bytes(body).await.map_err(unavailable)?;
+        let (headers, trailers) = split_trailers(parts.headers);
+        if parts.status != StatusCode::OK {
+            let pool = method.parent_pool();
+            return Err(Error::from(GrpcStatus {
+                headers,
+                trailers,
+                ..error_status(parts.status, &body, pool)
+            }));
+        }
+
+        let mut response = self.codec.decode(&method, &body)?;
+        response.set_headers(headers);
+        response.set_trailers(trailers);
+        Ok(response)
+    }
+
+    /// Opens a server streaming call. Returns the headers and
+    /// the stream of response messages.
+    ///
+    /// # Errors
+    /// - Failed to encode the request
+    /// - Server call failed
+    pub(super) async fn server_streaming(
+        &mut self,
+        req: &RequestMessage,
+    ) -> Result<(Metadata, ConnectStream)> {
+        let method = req.method_descriptor();
+        let body = envelope(0, &self.codec.encode(req)?);
+        let request = self.request(req, self.codec.streaming_content_type(), body)?;
+        let response = self.send(request).await?;
+        let (parts, body) = response.into_parts();
+        if parts.status != StatusCode::OK {
+            let body = hyper::body::to_bytes(body).await.map_err(unavailable)?;
+            let pool = method.parent_pool();
+            let (headers, trailers) = split_trailers(parts.headers);
+            return Err(Error::from(GrpcStatus {
+                headers,
+                trailers,
+                ..error_status(parts.status, &body, pool)
+            }));
+        }
+
+        let headers = Metadata::from(MetadataMap::from_headers(parts.headers));
+        let stream = ConnectStream {
+            body,
+            buf: BytesMut::new(),
+            method,
+            codec: self.codec,
+            max_message_size: self.max_message_size,
+            trailers: None,
+        };
+        Ok((headers, stream))
+    }
+
+    /// Builds the http request of a call.
+    fn request(
+        &self,
+        req: &RequestMessage,
+        content_type: &'static str,
+        body: Vec<u8>,
+    ) -> Result<Request<hyper::Body>> {
+        let mut headers = req
+            .metadata()
+            .as_ref()
+            .map(|meta| meta.inner.clone().into_headers())
+            .unwrap_or_default();
+        headers.insert(CONTENT_TYPE, HeaderValue::from_static(content_type));
+        headers.insert(
+            PROTOCOL_VERSION_HEADER,
+            HeaderValue::from_static(PROTOCOL_VERSION),
+        );
+        if let Some(timeout) = req.timeout() {
+            headers.insert(
+                TIMEOUT_HEADER,
+                HeaderValue::from(timeout.as_millis() as u64),
+            );
+        }
+
+        let mut request = Request::builder()
+            .method(Method::POST)
+            .uri(self.uri(req)?)
+            .body(hyper::Body::from(body))
+            .map_err(|err| Error::Internal(err.to_string()))?;
+        *request.headers_mut() = headers;
+        Ok(set_host(request, self.host.as_ref()))
+    }
+
+    /// Returns the uri of a call. A path of the origin is prepended
+    /// to the method path.
+    fn uri(&self, req: &RequestMessage) -> Result<Uri> {
+        let prefix = self.origin.path().trim_end_matches('/');
+        let mut parts = self.origin.clone().into_parts();
+        parts.path_and_query = Some(
+            format!("{prefix}{}", req.path())
+                .parse()
+                .map_err(|_| Error::Internal(String::from("Failed to build the request uri")))?,
+        );
+        Uri::from_parts(parts)
+            .map_err(|_| Error::Internal(String::from("Failed to build the request uri")))
+    }
+
+    async fn send(&mut self, request: Request<hyper::Body>) -> Result<Response<hyper::Body>> {
+        self.client
+            .ready()
+            .await
+            .map_err(unavailable)?
+            .call(request)
+            .await
+            .map_err(unavailable)
+    }
+}
+
+/// A stream of response messages of a Connect streaming call.
+#[derive(Debug)]
+pub(super) struct ConnectStream {
+    body: hyper::Body,
+    buf: BytesMut,
+    method: MethodDescriptor,
+    codec: Codec,
+    /// The maximum size of a message. Larger envelopes are rejected.
+    max_message_size: usize,
+    /// The trailers, set once the end of the stream is received.
+    trailers: Option<Metadata>,
+}
+
+impl ConnectStream {
+    /// Waits for the next message of the stream. Returns `None`
+    /// if the server closed the stream.
+    ///
+    /// # Errors
+    /// - Server sent an error
+    /// - The response is not a valid Connect stream
+    pub(super) async fn message(&mut self) -> Result<Option<ResponseMessage>> {
+        if self.trailers.is_some() {
+            return Ok(None);
+        }
+        loop {
+            if let Some((flags, data)) = self.next_envelope()? {
+                if flags & END_STREAM_FLAG != 0 {
+                    let end: EndStream = serde_json::from_slice(&data)
+                        .map_err(|err| Error::ConnectProtocolError(err.to_string()))?;
+                    let trailers = end.metadata();
+                    self.trailers = Some(trailers.clone());
+                    return match end.error {
+                        Some(error) => {
+                            let pool = self.method.parent_pool();
+                            Err(Error::from(GrpcStatus {
+                                trailers,
+                                ..error.into_status(pool)
+                            }))
+                        }
+                        None => Ok(None),
+                    };
+                }
+                if flags & COMPRESSED_FLAG != 0 {
+                    return Err(Error::ConnectProtocolError(String::from(
+                        "compressed messages are not supported",
+                    )));
+                }
+                return self.codec.decode(&self.method, &data).map(Some);
+            }
+            match self.body.data().await {
+                Some(chunk) => self.buf.extend_from_slice(&chunk.map_err(unavailable)?),
+                None => {
+                    return Err(Error::ConnectProtocolError(String::from(
+                        "stream closed without end of stream message",
+                    )))
+                }
+            }
+        }
+    }
+
+    /// Waits for the trailers of the stream. Drains the remaining
+    /// messages of the stream.
+    ///
+    /// # Errors
+    /// - Server sent an error
+    /// - The response is not a valid Connect stream
+    pub(super) async fn trailers(&mut self) -> Result<Metadata> {
+        while self.message().await?.is_some() {}
+        Ok(self.trailers.clone().unwrap_or_default())
+    }
+
+    /// Splits the next complete envelope off the buffer.
+    ///
+    /// # Errors
+    /// - The envelope is larger than the maximum message size
+    fn next_envelope(&mut self) -> Result<Option<(u8, Bytes)>> {
+        if self.buf.len() < ENVELOPE_PREFIX_LEN {
+            return Ok(None);
+        }
+        let len = u32::from_be_bytes([self.buf[1], self.buf[2], self.buf[3], self.buf[4]]);
+        let len = len as usize;
+        if len > self.max_message_size {
+            return Err(Error::from(GrpcStatus::new(
+                Code::OutOfRange,
+                format!(
+                    "decoded message length too large: found {len} bytes, the limit is: {} bytes",
+                    self.max_message_size
+                ),
+            )));
+        }
+        if self.buf.len() < ENVELOPE_PREFIX_LEN + len {
+            return Ok(None);
+        }
+        let flags = self.buf[0];
+        self.buf.advance(ENVELOPE_PREFIX_LEN);
+        Ok(Some((flags, self.buf.split_to(len).freeze())))
+    }
+}
+
+/// Wraps a message in the Connect envelope.
+pub(super) fn envelope(flags: u8, message: &[u8]) -> Vec<u8> {
+    let mut buf = Vec::with_capacity(ENVELOPE_PREFIX_LEN + message.len());
+    buf.push(flags);
+    buf.extend_from_slice(&(message.len() as u32).to_be_bytes());
+    buf.extend_from_slice(message);
+    buf
+}
+
+/// Splits the headers of a unary response into headers and the
+/// trailers, which are prefixed with `trailer-`.
+fn split_trailers(headers: HeaderMap) -> (Metadata, Metadata) {
+    let mut leading = HeaderMap::new();
+    let mut trailing = HeaderMap::new();
+    let mut name = None;
+    for (key, value) in headers {
+        // Only the first value of a header holds the name
+        name = key.or(name);
+        let Some(name) = &name else {
+            continue;
+        };
+        let trailer = name
+            .as_str()
+            .strip_prefix(TRAILER_PREFIX)
+            .and_then(|key| HeaderName::from_bytes(key.as_bytes()).ok());
+        match trailer {
+            Some(key) => trailing.append(key, value),
+            None => leading.append(name.clone(), value),
+        };
+    }
+    (
+        MetadataMap::from_headers(leading).into(),
+        MetadataMap::from_headers(trailing).into(),
+    )
+}
+
+/// Maps a transport error to an `Unavailable` status.
+fn unavailable(err: hyper::Error) -> Error {
+    Error::from(GrpcStatus::new(Code::Unavailable, err.to_string()))
+}
+
+/// Returns the status of a failed call from the json error of the
+/// body. Falls back to the http status if the body holds no error.
+fn error_status(status: StatusCode, body: &[u8], pool: &DescriptorPool) -> GrpcStatus {
+    match serde_json::from_slice::<ConnectError>(body) {
+        Ok(error) => error.into_status(pool),
+        Err(_) => GrpcStatus::new(code_from_http(status), String::from_utf8_lossy(body)),
+    }
+}
+
+/// Maps a http status to a status code as defined by the Connect
+/// protocol.
+fn code_from_http(status: StatusCode) -> Code {
+    match status.as_u16() {
+        400 => Code::Internal,
+        401 => Code::Unauthenticated,
+        403 => Code::PermissionDenied,
+        404 => Code::Unimplemented,
+        429 | 502 | 503 | 504 => Code::Unavailable,
+        _ => Code::Unknown,
+    }
+}
+
+/// The json error of a Connect call.
+#[derive(Debug, Deserialize)]
+struct ConnectError {
+    code: String,
+    #[serde(default)]
+    message: String,
+    #[serde(default)]
+    details: Vec<ConnectErrorDetail>,
+}
+
+/// A detail of a Connect error, a message of the given type
+/// which is base64 encoded.
+#[derive(Debug, Deserialize)]
+struct ConnectErrorDetail {
+    #[serde(rename = "type")]
+    type_name: String,
+    value: String,
+}
+
+impl ConnectError {
+    /// Converts the error into a status. The details are decoded like
+    /// the details of a `google.rpc.Status`, a detail which is not valid
+    /// base64 is kept with its value as sent.
+    fn into_status(self, pool: &DescriptorPool) -> GrpcStatus {
+        let code = code_from_str(&self.code).unwrap_or(Code::Unknown);
+        let details = Some(self.details)
+            .filter(|details| !details.is_empty())
+            .map(|details| {
+                let details = details
+                    .into_iter()
+                    .map(|detail| {
+                        let type_url = format!("type.googleapis.com/{}", detail.type_name);
+                        match BASE64.decode(&detail.value) {
+                            Ok(value) => decode_any(&Any { type_url, value }, Some(pool)),
+                            Err(_) => json!({"@type": type_url, "value": detail.value}),
+                        }
+                    })
+                    .collect();
+                status_json(code as i32, &self.message, details)
+            });
+        GrpcStatus {
+            details,
+            ..GrpcStatus::new(code, self.message)
+        }
+    }
+}
+
+/// The json message which ends a Connect stream.
+#[derive(Debug, Deserialize)]
+struct EndStream {
+    #[serde(default)]
+    error: Option<ConnectError>,
+    #[serde(default)]
+    metadata: HashMap<String, Vec<String>>,
+}
+
+impl EndStream {
+    /// Returns the trailers. Invalid keys or values are skipped.
+    fn metadata(&self) -> Metadata {
+        let mut headers = HeaderMap::new();
+        for (key, values) in &self.metadata {
+            let Ok(key) = HeaderName::from_bytes(key.as_bytes()) else {
+                continue;
+            };
+            for value in values {
+                if let Ok(value) = HeaderValue::from_str(value) {
+                    headers.append(key.clone(), value);
+                }
+            }
+        }
+        MetadataMap::from_headers(headers).into()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_split_trailers() {
+        // given
+        let mut headers = HeaderMap::new();
+        headers.insert("x-request-id", HeaderValue::from_static("42"));
+        headers.insert("trailer-server-timing", HeaderValue::from_static("10ms"));
+
+        // when
+        let (headers, trailers) = split_trailers(headers);
+
+        // then
+        assert_eq!(headers.get("x-request-id").unwrap(), "42");
+        assert!(headers.get("trailer-server-timing").is_none());
+        assert_eq!(trailers.get("server-timing").unwrap(), "10ms");
+    }
+
+    #[test]
+    fn test_error_status() {
+        // given
+        let pool = DescriptorPool::new();
+        let body = br#"{"code":"invalid_argument","message":"invalid number"}"#;
+
+        // when
+        let status = error_status(StatusCode::BAD_REQUEST, body, &pool);
+
+        // then
+        assert_eq!(status.code, Code::InvalidArgument);
+        assert_eq!(status.message, "invalid number");
+        assert!(status.details.is_none());
+    }
+
+    #[test]
+    fn test_error_status_from_http() {
+        // given
+        let pool = DescriptorPool::new();
+
+        // when
+        let status = error_status(
+            StatusCode::SERVICE_UNAVAILABLE,
+            b"no healthy upstream",
+            &pool,
+        );
+
+        // then
+        assert_eq!(status.code, Code::Unavailable);
+        assert_eq!(status.message, "no healthy upstream");
+    }
+
+    #[test]
+    fn test_error_status_details() {
+        // given
+        let pool = DescriptorPool::new();
+        let body = br#"{"code":"not_found","message":"unknown","details":[
+            {"type":"unknown.Type","value":"AQID"},
+            {"type":"unknown.Type","value":"not base64!"}
+        ]}"#;
+
+        // when
+        let status = error_status(StatusCode::NOT_FOUND, body, &pool);
+
+        // then
+        let expected = json!({
+            "code": 5,
+            "message": "unknown",
+            "details": [
+                {"@type": "type.googleapis.com/unknown.Type", "value": "AQID"},
+                {"@type": "type.googleapis.com/unknown.Type", "value": "not base64!"},
+            ],
+        });
+        assert_eq!(status.details, Some(expected));
+    }
+}
@@ -0,0 +1,153 @@
+//! The settings of the connections of the grpc client.
+#![allow(clippy::module_name_repetitions)]
+use super::endpoint::EndpointConfig;
+use super::proxy::ProxyConfig;
+use super::tls::TlsConfig;
+use crate::error::Result;
+use hyper::client::HttpConnector;
+use hyper_proxy::ProxyConnector;
+use hyper_rustls::HttpsConnector;
+
+/// The connection config of the grpc client. Holds the TLS, proxy and
+/// endpoint settings.
+#[derive(Debug, Clone, Default, PartialEq, Eq, PartialOrd, Hash)]
+pub struct ConnectionConfig {
+    /// The TLS settings
+    tls: TlsConfig,
+    /// The proxy through which the connections are tunneled
+    proxy: ProxyConfig,
+    /// The tuning of the connections and the message size limits
+    endpoint: EndpointConfig,
+}
+
+impl ConnectionConfig {
+    /// Create a new `ConnectionConfig` with the given TLS settings.
+    #[must_use]
+    pub fn new(tls: TlsConfig) -> Self {
+        Self {
+            tls,
+            ..Default::default()
+        }
+    }
+
+    /// Sets the TLS settings.
+    #[must_use]
+    pub fn with_tls(mut self, tls: TlsConfig) -> Self {
+        self.tls = tls;
+        self
+    }
+
+    /// Returns the TLS settings.
+    #[must_use]
+    pub fn tls(&self) -> &TlsConfig {
+        &self.tls
+    }
+
+    /// Tunnels the connections through a HTTP CONNECT proxy. Applies
+    /// to `http` and `https` addresses.
+    #[must_use]
+    pub fn with_proxy(mut self, proxy: ProxyConfig) -> Self {
+        self.proxy = proxy;
+        self
+    }
+
+    /// Returns the proxy settings.
+    #[must_use]
+    pub fn proxy(&self) -> &ProxyConfig {
+        &self.proxy
+    }
+
+    /// Sets the tuning of the connections, e.g. the keepalive and the
+    /// message size limits.
+    #[must_use]
+    pub fn with_endpoint(mut self, endpoint: EndpointConfig) -> Self {
+        self.endpoint = endpoint;
+        self
+    }
+
+    /// Returns the tuning of the connections.
+    #[must_use]
+    pub fn endpoint(&self) -> &EndpointConfig {
+        &self.endpoint
+    }
+
+    /// Get the HTTPS connector based on the TLS configuration.
+    ///
+    /// # Errors
+    /// - Failed to load the tls certificates or the client key
+    /// - The authority override is invalid
+    /// - The proxy uri is invalid
+    pub fn get_connector_from_tls(&self) -> Result<HttpsConnector<ProxyConnector<HttpConnector>>> {
+        let builder = self.tls.connector_builder()?;
+        Ok(builder
+            .enable_http2()
+            .wrap_connector(self.get_proxy_connector()?))
+    }
+
+    /// Get the HTTPS connector based on the TLS configuration which
+    /// negotiates HTTP/1.1, as used by `gRPC-Web`.
+    ///
+    /// # Errors
+    /// - Failed to load the tls certificates or the client key
+    /// - The authority override is invalid
+    /// - The proxy uri is invalid
+    pub fn get_http1_connector_from_tls(
+        &self,
+    ) -> Result<HttpsConnector<ProxyConnector<HttpConnector>>> {
+        let builder = self.tls.connector_builder()?;
+        Ok(builder
+            .enable_http1()
+            .wrap_connector(self.get_proxy_connector()?))
+    }
+
+    /// Get the plaintext connector which tunnels the connections through
+    /// the proxy, if any.
+    ///
+    /// # Errors
+    /// - The proxy uri is invalid
+    pub fn get_proxy_connector(&self) -> Result<ProxyConnector<HttpConnector>> {
+        self.proxy.connector(self.http_connector())
+    }
+
+    /// Returns the tcp connector with the socket settings of the
+    /// endpoint config.
+    fn http_connector(&self) -> HttpConnector {
+        let mut http = HttpConnector::new();
+        http.enforce_http(false);
+        self.endpoint.apply_connector(&mut http);
+        http
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::error::Error;
+
+    #[test]
+    fn test_invalid_authority() {
+        // given
+        let connection = ConnectionConfig::new(
+            TlsConfig::default().with_authority(Some("in valid".to_string())),
+        );
+
+        // when
+        let result = connection.get_connector_from_tls();
+
+        // then
+        assert!(matches!(result, Err(Error::InvalidAuthority(_))));
+    }
+
+    #[test]
+    fn test_invalid_proxy() {
+        // given
+        let connection = ConnectionConfig::default()
+            .with_proxy(ProxyConfig::new(Some("http://in valid".to_string())));
+
+        // when
+        let result = connection.get_proxy_connector();
+
+        // then
+        assert!(matches!(result, Err(Error::InvalidProxy(_))));
+    }
+}
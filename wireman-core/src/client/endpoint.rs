@@ -0,0 +1,84 @@
+//! The tuning of the HTTP/2 connections and of the message sizes.
+use hyper::client::HttpConnector;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use tonic::client::Grpc;
+use tonic::transport::Endpoint;
+
+/// The default maximum size of a decoded response message, as in tonic.
+const DEFAULT_MAX_DECODING_MESSAGE_SIZE: usize = 4 * 1024 * 1024;
+
+/// The settings of the connections to a server. Unset values keep the
+/// defaults of tonic.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Hash, Serialize, Deserialize)]
+pub struct EndpointConfig {
+    /// The timeout of establishing a connection.
+    pub connect_timeout: Option<Duration>,
+    /// The interval of the HTTP/2 keepalive pings. Pings are sent
+    /// while the connection is idle as well, e.g. to keep long-running
+    /// streams alive through NATs.
+    pub keepalive_interval: Option<Duration>,
+    /// The time to wait for the acknowledgement of a keepalive ping
+    /// before the connection is closed.
+    pub keepalive_timeout: Option<Duration>,
+    /// The initial HTTP/2 stream window size in bytes.
+    pub initial_stream_window_size: Option<u32>,
+    /// The initial HTTP/2 connection window size in bytes.
+    pub initial_connection_window_size: Option<u32>,
+    /// Whether `TCP_NODELAY` is set on the sockets. Defaults to true.
+    pub tcp_nodelay: Option<bool>,
+    /// The maximum size of a decoded response message in bytes of
+    /// `gRPC` and `gRPC-Web` calls and of the messages of Connect
+    /// streams. Defaults to 4 MB.
+    pub max_decoding_message_size: Option<usize>,
+    /// The maximum size of an encoded request message in bytes of
+    /// `gRPC` and `gRPC-Web` calls. Defaults to no limit.
+    pub max_encoding_message_size: Option<usize>,
+}
+
+impl EndpointConfig {
+    /// Applies the connection settings to a tonic endpoint.
+    pub(super) fn apply_endpoint(&self, mut endpoint: Endpoint) -> Endpoint {
+        if let Some(timeout) = self.connect_timeout {
+            endpoint = endpoint.connect_timeout(timeout);
+        }
+        if let Some(interval) = self.keepalive_interval {
+            endpoint = endpoint
+                .http2_keep_alive_interval(interval)
+                .keep_alive_while_idle(true);
+        }
+        if let Some(timeout) = self.keepalive_timeout {
+            endpoint = endpoint.keep_alive_timeout(timeout);
+        }
+        if let Some(tcp_nodelay) = self.tcp_nodelay {
+            endpoint = endpoint.tcp_nodelay(tcp_nodelay);
+        }
+        endpoint
+            .initial_stream_window_size(self.initial_stream_window_size)
+            .initial_connection_window_size(self.initial_connection_window_size)
+    }
+
+    /// Applies the socket settings to a connector. Used by the custom
+    /// connectors, to which tonic does not apply them.
+    pub(super) fn apply_connector(&self, connector: &mut HttpConnector) {
+        connector.set_connect_timeout(self.connect_timeout);
+        connector.set_nodelay(self.tcp_nodelay.unwrap_or(true));
+    }
+
+    /// Returns the maximum size of a decoded response message.
+    pub(super) fn max_decoding_message_size(&self) -> usize {
+        self.max_decoding_message_size
+            .unwrap_or(DEFAULT_MAX_DECODING_MESSAGE_SIZE)
+    }
+
+    /// Applies the message size limits to a `gRPC` client.
+    pub(super) fn apply_grpc<T>(&self, mut grpc: Grpc<T>) -> Grpc<T> {
+        if let Some(limit) = self.max_decoding_message_size {
+            grpc = grpc.max_decoding_message_size(limit);
+        }
+        if let Some(limit) = self.max_encoding_message_size {
+            grpc = grpc.max_encoding_message_size(limit);
+        }
+        grpc
+    }
+}
@@ -0,0 +1,206 @@
+//! The `grpc.health.v1` health checking protocol. The health proto is
+//! embedded, so that servers can be checked without configuring it.
+use super::{GrpcClient, ResponseStream};
+use crate::descriptor::{embedded, RequestMessage, ResponseMessage};
+use crate::error::Error;
+use crate::Result;
+use prost_reflect::{DescriptorPool, Value};
+use std::fmt;
+use std::sync::OnceLock;
+
+/// The embedded proto file of the health service.
+const HEALTH_FILE: &str = "grpc/health/v1/health.proto";
+
+/// The full name of the health service.
+const HEALTH_SERVICE: &str = "grpc.health.v1.Health";
+
+/// Returns the descriptor pool of the health service. The pool is
+/// compiled once, a failure is returned on every call.
+///
+/// # Errors
+/// - Failed to compile the embedded health proto
+fn health_pool() -> Result<&'static DescriptorPool> {
+    static POOL: OnceLock<std::result::Result<DescriptorPool, String>> = OnceLock::new();
+    POOL.get_or_init(|| embedded::compile(&[HEALTH_FILE]).map_err(|err| err.to_string()))
+        .as_ref()
+        .map_err(|err| Error::Internal(format!("Failed to load the health proto: {err}")))
+}
+
+/// The methods of the health service.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HealthMethod {
+    /// Returns the current serving status.
+    Check,
+    /// Streams the serving status, first the current one and then
+    /// every change.
+    Watch,
+}
+
+impl HealthMethod {
+    fn name(self) -> &'static str {
+        match self {
+            Self::Check => "Check",
+            Self::Watch => "Watch",
+        }
+    }
+}
+
+/// Returns the request of a health method. An empty service checks
+/// the overall health of the server. Address, metadata and timeout
+/// are set by the caller as for any other request.
+///
+/// # Errors
+/// - Failed to compile the embedded health proto
+pub fn health_request(method: HealthMethod, service: &str) -> Result<RequestMessage> {
+    let method = health_pool()?
+        .get_service_by_name(HEALTH_SERVICE)
+        .and_then(|health| health.methods().find(|m| m.name() == method.name()))
+        .ok_or_else(|| Error::Internal(format!("Health method not found: {}", method.name())))?;
+    let mut req = RequestMessage::new(method.input(), method);
+    req.message_mut()
+        .set_field_by_name("service", Value::String(service.to_string()));
+    Ok(req)
+}
+
+/// The serving status of a server or service.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ServingStatus {
+    /// The status is not known.
+    Unknown,
+    /// The service is healthy.
+    Serving,
+    /// The service is not healthy.
+    NotServing,
+    /// The service is not known to the server. Sent by `Watch` only,
+    /// `Check` fails with `NOT_FOUND` instead.
+    ServiceUnknown,
+}
+
+impl ServingStatus {
+    /// Returns the serving status of a health response.
+    #[must_use]
+    pub fn from_response(resp: &ResponseMessage) -> Self {
+        match resp.message.get_field_by_name("status").as_deref() {
+            Some(Value::EnumNumber(1)) => Self::Serving,
+            Some(Value::EnumNumber(2)) => Self::NotServing,
+            Some(Value::EnumNumber(3)) => Self::ServiceUnknown,
+            _ => Self::Unknown,
+        }
+    }
+}
+
+impl fmt::Display for ServingStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Unknown => write!(f, "UNKNOWN"),
+            Self::Serving => write!(f, "SERVING"),
+            Self::NotServing => write!(f, "NOT_SERVING"),
+            Self::ServiceUnknown => write!(f, "SERVICE_UNKNOWN"),
+        }
+    }
+}
+
+/// The stream of serving status updates of a `Watch` call.
+#[derive(Debug)]
+pub struct HealthWatch(ResponseStream);
+
+impl HealthWatch {
+    /// Waits for the next serving status. Returns `None` if the
+    /// server closed the stream.
+    ///
+    /// # Errors
+    /// - Server sent an error status
+    /// - The deadline of the call exceeded
+    pub async fn status(&mut self) -> Result<Option<ServingStatus>> {
+        let message = self.0.message().await?;
+        Ok(message.as_ref().map(ServingStatus::from_response))
+    }
+}
+
+impl GrpcClient {
+    /// Calls `grpc.health.v1.Health/Check` with a request of
+    /// [`health_request`].
+    ///
+    /// # Errors
+    /// - `gRPC` client is not ready
+    /// - Server call failed, e.g. `NOT_FOUND` for an unknown service
+    pub async fn health_check(&mut self, req: &RequestMessage) -> Result<ServingStatus> {
+        let resp = self.unary(req).await?;
+        Ok(ServingStatus::from_response(&resp))
+    }
+
+    /// Calls `grpc.health.v1.Health/Watch` with a request of
+    /// [`health_request`].
+    ///
+    /// # Errors
+    /// - `gRPC` client is not ready
+    /// - Server call failed
+    pub async fn health_watch(&mut self, req: &RequestMessage) -> Result<HealthWatch> {
+        Ok(HealthWatch(self.server_streaming(req).await?))
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod test {
+    use super::*;
+    use crate::client::transport::Transport;
+    use tokio::net::TcpListener;
+    use tokio_stream::wrappers::TcpListenerStream;
+    use tonic_health::server::HealthReporter;
+
+    /// Serves the health service. The service `test` is serving, all
+    /// other services are unknown.
+    pub(crate) async fn serve_health() -> (String, HealthReporter) {
+        let (mut reporter, service) = tonic_health::server::health_reporter();
+        reporter
+            .set_service_status("test", tonic_health::ServingStatus::Serving)
+            .await;
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = tonic::transport::Server::builder()
+            .add_service(service)
+            .serve_with_incoming(TcpListenerStream::new(listener));
+        tokio::spawn(server);
+        (format!("http://{addr}"), reporter)
+    }
+
+    #[tokio::test]
+    async fn test_health_check() {
+        // given
+        let (address, _reporter) = serve_health().await;
+        let mut client = GrpcClient::from_address(&address, None, Transport::Grpc).unwrap();
+        let req = health_request(HealthMethod::Check, "test").unwrap();
+        let unknown = health_request(HealthMethod::Check, "unknown").unwrap();
+
+        // when
+        let status = client.health_check(&req).await.unwrap();
+        let result = client.health_check(&unknown).await;
+
+        // then
+        assert_eq!(status, ServingStatus::Serving);
+        let Err(Error::GrpcError(status)) = result else {
+            panic!("expected grpc error, got {result:?}");
+        };
+        assert_eq!(status.code, tonic::Code::NotFound);
+    }
+
+    #[tokio::test]
+    async fn test_health_watch() {
+        // given
+        let (address, mut reporter) = serve_health().await;
+        let mut client = GrpcClient::from_address(&address, None, Transport::Grpc).unwrap();
+        let req = health_request(HealthMethod::Watch, "test").unwrap();
+
+        // when
+        let mut watch = client.health_watch(&req).await.unwrap();
+        let first = watch.status().await.unwrap();
+        reporter
+            .set_service_status("test", tonic_health::ServingStatus::NotServing)
+            .await;
+        let second = watch.status().await.unwrap();
+
+        // then
+        assert_eq!(first, Some(ServingStatus::Serving));
+        assert_eq!(second, Some(ServingStatus::NotServing));
+    }
+}
@@ -0,0 +1,210 @@
+//! The timings and sizes of calls.
+use http::Uri;
+use serde::{Deserialize, Serialize};
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+use std::time::Duration;
+use tokio::time::Instant;
+use tower::Service;
+
+/// The timings and the encoded message sizes of a call. Timings are
+/// measured from the start of the last attempt of the call.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Metrics {
+    /// The time to establish a new connection, including the proxy
+    /// tunnel and the TLS handshake. `None` if an open connection
+    /// was reused.
+    #[serde(default, with = "optional_millis")]
+    pub connect: Option<Duration>,
+    /// The time until the response headers were received. `None` for
+    /// unary calls of the Connect protocol, whose headers and body
+    /// arrive together.
+    #[serde(default, with = "optional_millis")]
+    pub first_byte: Option<Duration>,
+    /// The time until the response was complete. For streams, the
+    /// time until the last received message.
+    #[serde(with = "millis")]
+    pub total: Duration,
+    /// The encoded size of the request messages in bytes. For
+    /// bidirectional streams, the size of the first message.
+    pub request_size: usize,
+    /// The encoded size of the response messages in bytes.
+    pub response_size: usize,
+}
+
+impl Metrics {
+    /// Returns the metrics of a call that was sent at `start`, whose
+    /// response headers arrived now.
+    pub(super) fn started(start: Instant, connect: Option<Duration>, request_size: usize) -> Self {
+        Self {
+            connect,
+            first_byte: Some(start.elapsed()),
+            total: start.elapsed(),
+            request_size,
+            response_size: 0,
+        }
+    }
+}
+
+/// The last connection established by a client. The calls which
+/// started before the connection was established report its duration,
+/// so that concurrent calls of the clones of a client do not race.
+#[derive(Debug, Clone, Default)]
+pub(super) struct ConnectTime(Arc<Mutex<Option<Connect>>>);
+
+/// A connection, with the time it was established and the time it took.
+#[derive(Debug, Clone, Copy)]
+struct Connect {
+    established: Instant,
+    duration: Duration,
+}
+
+impl ConnectTime {
+    /// Returns the duration of the last connection if it was established
+    /// after `start`, i.e. the call which started at `start` waited for it.
+    pub(super) fn since(&self, start: Instant) -> Option<Duration> {
+        let connect = (*self.0.lock().ok()?)?;
+        (connect.established >= start).then_some(connect.duration)
+    }
+
+    /// Wraps a connector so that its connections are timed.
+    pub(super) fn wrap<C>(&self, inner: C) -> ConnectTimer<C> {
+        ConnectTimer {
+            inner,
+            elapsed: self.clone(),
+        }
+    }
+
+    fn set(&self, duration: Duration) {
+        if let Ok(mut connect) = self.0.lock() {
+            *connect = Some(Connect {
+                established: Instant::now(),
+                duration,
+            });
+        }
+    }
+}
+
+/// A connector which records the time it takes to establish a
+/// connection.
+#[derive(Debug, Clone)]
+pub(super) struct ConnectTimer<C> {
+    inner: C,
+    elapsed: ConnectTime,
+}
+
+impl<C> Service<Uri> for ConnectTimer<C>
+where
+    C: Service<Uri>,
+    C::Future: Send + 'static,
+{
+    type Response = C::Response;
+    type Error = C::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<C::Response, C::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, uri: Uri) -> Self::Future {
+        let start = Instant::now();
+        let future = self.inner.call(uri);
+        let elapsed = self.elapsed.clone();
+        Box::pin(async move {
+            let connection = future.await?;
+            elapsed.set(start.elapsed());
+            Ok(connection)
+        })
+    }
+}
+
+/// (De)serializes a duration as fractional milliseconds.
+pub(crate) mod millis {
+    use serde::{Deserialize, Deserializer, Serializer};
+    use std::time::Duration;
+
+    pub(crate) fn serialize<S: Serializer>(duration: &Duration, s: S) -> Result<S::Ok, S::Error> {
+        s.serialize_f64(duration.as_secs_f64() * 1000.0)
+    }
+
+    pub(crate) fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<Duration, D::Error> {
+        f64::deserialize(d).map(from_millis)
+    }
+
+    /// Returns the duration of fractional milliseconds, rounded to
+    /// nanoseconds.
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    pub(super) fn from_millis(millis: f64) -> Duration {
+        Duration::from_nanos((millis.max(0.0) * 1_000_000.0).round() as u64)
+    }
+}
+
+/// (De)serializes an optional duration as fractional milliseconds.
+mod optional_millis {
+    use serde::{Deserialize, Deserializer, Serializer};
+    use std::time::Duration;
+
+    pub(super) fn serialize<S: Serializer>(
+        duration: &Option<Duration>,
+        s: S,
+    ) -> Result<S::Ok, S::Error> {
+        match duration {
+            Some(duration) => super::millis::serialize(duration, s),
+            None => s.serialize_none(),
+        }
+    }
+
+    pub(super) fn deserialize<'de, D: Deserializer<'de>>(
+        d: D,
+    ) -> Result<Option<Duration>, D::Error> {
+        Ok(Option::<f64>::deserialize(d)?.map(super::millis::from_millis))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_serialize_metrics() {
+        // given
+        let metrics = Metrics {
+            connect: None,
+            first_byte: Some(Duration::from_micros(12_500)),
+            total: Duration::from_millis(40),
+            request_size: 7,
+            response_size: 128,
+        };
+
+        // when
+        let json = serde_json::to_string(&metrics).unwrap();
+        let parsed: Metrics = serde_json::from_str(&json).unwrap();
+
+        // then
+        assert_eq!(
+            json,
+            r#"{"connect":null,"first_byte":12.5,"total":40.0,"request_size":7,"response_size":128}"#
+        );
+        assert_eq!(parsed, metrics);
+    }
+
+    #[test]
+    fn test_connect_time_since() {
+        // given
+        let connect_time = ConnectTime::default();
+        let before = Instant::now();
+        connect_time.set(Duration::from_millis(5));
+        let after = Instant::now() + Duration::from_millis(1);
+
+        // when
+        let waited = connect_time.since(before);
+        let reused = connect_time.since(after);
+
+        // then
+        assert_eq!(waited, Some(Duration::from_millis(5)));
+        assert_eq!(waited, connect_time.since(before));
+        assert_eq!(reused, None);
+    }
+}
@@ -1,54 +1,832 @@
 #![allow(clippy::module_name_repetitions)]
 //! Module for all grpc related stuff
-mod codec;
+pub(crate) mod codec;
+pub mod compression;
+mod connect;
+pub mod connection;
+pub mod endpoint;
+pub mod health;
+pub mod metrics;
+pub mod proxy;
+mod reflection;
+pub mod service_config;
 pub mod tls;
+pub mod transport;
 
+use crate::descriptor::metadata::Metadata;
 use crate::descriptor::RequestMessage;
 use crate::descriptor::ResponseMessage;
-use crate::error::Error;
+use crate::error::{Error, GrpcStatus};
 use crate::Result;
-use tls::TlsConfig;
+use compression::Compression;
+use connect::{Codec, ConnectClient, ConnectStream};
+use connection::ConnectionConfig;
+use http::uri::Scheme;
+use metrics::{ConnectTime, Metrics};
+use prost_reflect::DescriptorPool;
+use service_config::{Retry, ServiceConfig};
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use std::time::Duration;
+#[cfg(unix)]
+use tokio::net::UnixStream;
 use tokio::runtime::Runtime;
+use tokio::sync::mpsc;
+use tokio::time::Instant;
+use tokio_stream::wrappers::UnboundedReceiverStream;
 use tonic::transport::Uri;
 use tonic::{client::Grpc, transport::Channel};
+use tonic::{Code, Request, Response, Streaming};
+#[cfg(unix)]
+use tower::service_fn;
+use transport::{HttpService, Transport};
 
 /// Represents a `gRPC` client for making RPC calls.
-#[derive(Clone, Debug)]
+#[derive(Clone)]
 pub struct GrpcClient {
-    grpc: Grpc<Channel>,
+    inner: Inner,
+    transport: Transport,
+    /// The timeouts and retry policies of the methods.
+    service_config: Option<Arc<ServiceConfig>>,
+    /// The number of attempts of the last call.
+    attempts: u32,
+    /// The duration of the last established connection.
+    connect_time: ConnectTime,
+}
+
+/// The client which sends the calls of a [`GrpcClient`].
+#[derive(Clone)]
+enum Inner {
+    /// A `gRPC` or `gRPC-Web` client.
+    Grpc(Grpc<HttpService>),
+    /// A Connect client.
+    Connect(ConnectClient),
+}
+
+impl std::fmt::Debug for GrpcClient {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("GrpcClient")
+            .field("transport", &self.transport)
+            .finish_non_exhaustive()
+    }
 }
 
 impl GrpcClient {
-    /// Returns a new Grpc Client. if no tls is given, the standard tonic
-    /// client is used.
-    pub fn new<T: Into<Uri>>(uri: T, tls: Option<TlsConfig>) -> Self {
-        let builder = Channel::builder(uri.into());
-        let channel = if let Some(tls) = tls {
+    /// Returns a new Grpc Client. The connector is chosen from the scheme
+    /// of the uri: `https` addresses are connected via TLS, using the given
+    /// tls settings or the default webpki roots. All other addresses are
+    /// connected in plaintext. The `:authority` override of the tls
+    /// settings is applied to both. The responses hold the timings and
+    /// message sizes of the calls.
+    ///
+    /// # Errors
+    /// - Failed to load the tls certificates or the client key
+    /// - The authority override is invalid
+    pub fn new<T: Into<Uri>>(uri: T, connection: Option<ConnectionConfig>) -> Result<Self> {
+        let uri = uri.into();
+        let connection = connection.unwrap_or_default();
+        let is_https = uri.scheme() == Some(&Scheme::HTTPS);
+        let mut builder = connection
+            .endpoint()
+            .apply_endpoint(Channel::builder(uri.clone()));
+        if let Some(authority) = connection.tls().authority() {
+            builder = builder.origin(origin(&uri, authority)?);
+        }
+        let connect_time = ConnectTime::default();
+        let channel = if is_https {
             // Build a channel with custom tls settings
-            let connector = tls.get_connector_from_tls();
-            builder.connect_with_connector_lazy(connector)
+            let connector = connection.get_connector_from_tls()?;
+            builder.connect_with_connector_lazy(connect_time.wrap(connector))
         } else {
-            // The standard tonic channel
-            builder.connect_lazy()
+            // Plaintext connections, tunneled through the proxy if set
+            let connector = connection.get_proxy_connector()?;
+            builder.connect_with_connector_lazy(connect_time.wrap(connector))
         };
 
-        GrpcClient {
-            grpc: Grpc::new(channel),
+        Ok(GrpcClient {
+            inner: Inner::Grpc(
+                connection
+                    .endpoint()
+                    .apply_grpc(Grpc::new(transport::grpc(channel))),
+            ),
+            transport: Transport::Grpc,
+            service_config: None,
+            attempts: 0,
+            connect_time,
+        })
+    }
+
+    /// Returns a new Grpc Client which speaks `gRPC-Web` over HTTP/1.1.
+    /// `https` addresses are connected via TLS. A path of the uri is
+    /// prepended to the method paths, e.g. for gateways that serve
+    /// `gRPC-Web` under a prefix.
+    ///
+    /// # Errors
+    /// - Failed to load the tls certificates or the client key
+    /// - The authority override is invalid
+    pub fn new_grpc_web<T: Into<Uri>>(
+        uri: T,
+        connection: Option<ConnectionConfig>,
+    ) -> Result<Self> {
+        let uri = uri.into();
+        let connection = connection.unwrap_or_default();
+        let connect_time = ConnectTime::default();
+        let service = transport::grpc_web(&uri, &connection, &connect_time)?;
+        Ok(GrpcClient {
+            inner: Inner::Grpc(
+                connection
+                    .endpoint()
+                    .apply_grpc(Grpc::with_origin(service, uri)),
+            ),
+            transport: Transport::GrpcWeb,
+            service_config: None,
+            attempts: 0,
+            connect_time,
+        })
+    }
+
+    /// Returns a new Grpc Client which speaks the Connect protocol over
+    /// HTTP/1.1. Messages are encoded as json if `json` is set, else as
+    /// binary protobuf. `https` addresses are connected via TLS. A path
+    /// of the uri is prepended to the method paths.
+    ///
+    /// # Errors
+    /// - Failed to load the tls certificates or the client key
+    /// - The authority override is invalid
+    pub fn new_connect<T: Into<Uri>>(
+        uri: T,
+        connection: Option<ConnectionConfig>,
+        json: bool,
+    ) -> Result<Self> {
+        let (codec, transport) = if json {
+            (Codec::Json, Transport::ConnectJson)
+        } else {
+            (Codec::Proto, Transport::Connect)
+        };
+        let connect_time = ConnectTime::default();
+        let client = ConnectClient::new(
+            uri.into(),
+            &connection.unwrap_or_default(),
+            codec,
+            &connect_time,
+        )?;
+        Ok(GrpcClient {
+            inner: Inner::Connect(client),
+            transport,
+            service_config: None,
+            attempts: 0,
+            connect_time,
+        })
+    }
+
+    /// Returns a new Grpc Client which connects to a Unix domain socket.
+    /// The `:authority` override of the tls settings is applied, TLS is
+    /// not used.
+    ///
+    /// # Errors
+    /// - The authority override is invalid
+    #[cfg(unix)]
+    pub fn new_unix(path: &str, connection: Option<ConnectionConfig>) -> Result<Self> {
+        // The uri is ignored by the connector, but determines the origin
+        let uri = Uri::from_static("http://localhost");
+        let connection = connection.unwrap_or_default();
+        let origin = origin(&uri, connection.tls().authority().unwrap_or("localhost"))?;
+        let path = path.to_string();
+        let connector = service_fn(move |_: Uri| UnixStream::connect(path.clone()));
+        let connect_time = ConnectTime::default();
+        let channel = connection
+            .endpoint()
+            .apply_endpoint(Channel::builder(uri))
+            .origin(origin)
+            .connect_with_connector_lazy(connect_time.wrap(connector));
+
+        Ok(GrpcClient {
+            inner: Inner::Grpc(
+                connection
+                    .endpoint()
+                    .apply_grpc(Grpc::new(transport::grpc(channel))),
+            ),
+            transport: Transport::Grpc,
+            service_config: None,
+            attempts: 0,
+            connect_time,
+        })
+    }
+
+    /// Returns a new Grpc Client from an address string which speaks
+    /// the given transport. Addresses of the form `unix:///path/to/socket`
+    /// connect to a Unix domain socket, which supports only native `gRPC`.
+    ///
+    /// # Errors
+    /// - Failed to parse the address
+    /// - Failed to load the tls certificates or the client key
+    /// - The transport is not supported for Unix domain sockets
+    pub fn from_address(
+        address: &str,
+        connection: Option<ConnectionConfig>,
+        transport: Transport,
+    ) -> Result<Self> {
+        if let Some(path) = unix_socket_path(address) {
+            if transport != Transport::Grpc {
+                return Err(Error::Internal(format!(
+                    "{transport} is not supported for Unix domain sockets"
+                )));
+            }
+            #[cfg(unix)]
+            return Self::new_unix(path, connection);
+            #[cfg(not(unix))]
+            return Err(Error::Internal(String::from(
+                "Unix domain sockets are not supported",
+            )));
+        }
+        let uri = Uri::try_from(address)
+            .map_err(|_| Error::Internal(String::from("Failed to parse address")))?;
+        match transport {
+            Transport::Grpc => Self::new(uri, connection),
+            Transport::GrpcWeb => Self::new_grpc_web(uri, connection),
+            Transport::Connect => Self::new_connect(uri, connection, false),
+            Transport::ConnectJson => Self::new_connect(uri, connection, true),
+        }
+    }
+
+    /// Applies the compression to the calls of the client. It is
+    /// ignored for the Connect protocol.
+    #[must_use]
+    pub fn with_compression(mut self, compression: Compression) -> Self {
+        if let Inner::Grpc(grpc) = self.inner {
+            self.inner = Inner::Grpc(compression.apply(grpc));
+        }
+        self
+    }
+
+    /// Applies the timeouts and retry policies of the service config
+    /// to the unary, server streaming and client streaming calls.
+    #[must_use]
+    pub fn with_service_config(mut self, service_config: Arc<ServiceConfig>) -> Self {
+        self.service_config = Some(service_config);
+        self
+    }
+
+    /// Returns the number of attempts of the last call, including
+    /// the retries.
+    #[must_use]
+    pub fn attempts(&self) -> u32 {
+        self.attempts
+    }
+
+    /// Returns the retry state of a call as defined by the service config.
+    fn retry(&self, req: &RequestMessage) -> Retry {
+        let config = self
+            .service_config
+            .as_ref()
+            .and_then(|config| config.method_config(&req.method_descriptor()))
+            .cloned();
+        Retry::new(config, req.timeout())
+    }
+
+    /// Returns the transport of the client.
+    #[must_use]
+    pub fn transport(&self) -> Transport {
+        self.transport
+    }
+
+    /// Fails if the transport does not support client streaming.
+    fn check_client_streaming(&self) -> Result<()> {
+        if self.transport.supports_client_streaming() {
+            return Ok(());
         }
+        Err(Error::UnsupportedStreaming(self.transport))
     }
 
-    /// Make a unary `gRPC` call from the client.
+    /// Returns the `gRPC` client. Fails for the Connect protocol.
+    fn grpc(&mut self) -> Result<&mut Grpc<HttpService>> {
+        match &mut self.inner {
+            Inner::Grpc(grpc) => Ok(grpc),
+            Inner::Connect(_) => Err(Error::Internal(format!(
+                "gRPC calls are not supported over {}",
+                self.transport
+            ))),
+        }
+    }
+
+    /// Make a unary `gRPC` call from the client. The response holds
+    /// the headers and trailers sent by the server. Failed calls are
+    /// retried as defined by the service config.
     ///
     /// # Errors
     /// - `gRPC` client is not ready
     /// - Server call failed
     pub async fn unary(&mut self, req: &RequestMessage) -> Result<ResponseMessage> {
-        self.grpc.ready().await.map_err(Error::GrpcNotReady)?;
+        let mut retry = self.retry(req);
+        loop {
+            let result = self.unary_attempt(&retry.request(req)).await;
+            if let Err(err) = &result {
+                if retry.retry(err).await {
+                    continue;
+                }
+            }
+            self.attempts = retry.attempts();
+            return result.map(|mut response| {
+                response.set_attempts(retry.attempts());
+                response
+            });
+        }
+    }
+
+    /// Makes a single attempt of a unary call.
+    async fn unary_attempt(&mut self, req: &RequestMessage) -> Result<ResponseMessage> {
+        let deadline = Deadline::new(req.timeout());
+        let start = Instant::now();
+        let request_size = req.encoded_len();
+        if let Inner::Connect(client) = &mut self.inner {
+            let mut response = with_deadline(deadline, client.unary(req)).await?;
+            response.set_metrics(Metrics {
+                connect: self.connect_time.since(start),
+                first_byte: None,
+                total: start.elapsed(),
+                request_size,
+                response_size: response.encoded_len(),
+            });
+            return Ok(response);
+        }
+        let pool = req.method_descriptor().parent_pool().clone();
+        let grpc = self.grpc()?;
+        let stream = with_deadline(deadline, async {
+            grpc.ready().await.map_err(Error::GrpcNotReady)?;
+            let codec = codec::DynamicCodec::new(req.method_descriptor());
+            let path = req.path();
+            let request = req.clone().into_request();
+            let response = grpc
+                .server_streaming(request, path, codec)
+                .await
+                .map_err(|status| grpc_error(&status, &pool))?;
+            Ok(response)
+        })
+        .await?;
+        let metrics = Metrics::started(start, self.connect_time.since(start), request_size);
+        ResponseStream::new(stream, deadline, pool, start, metrics)
+            .single_message()
+            .await
+    }
+
+    /// Make a server streaming `gRPC` call from the client. Opening
+    /// the stream is retried as defined by the service config.
+    ///
+    /// # Errors
+    /// - `gRPC` client is not ready
+    /// - Server call failed
+    pub async fn server_streaming(&mut self, req: &RequestMessage) -> Result<ResponseStream> {
+        let mut retry = self.retry(req);
+        loop {
+            let result = self.server_streaming_attempt(&retry.request(req)).await;
+            if let Err(err) = &result {
+                if retry.retry(err).await {
+                    continue;
+                }
+            }
+            self.attempts = retry.attempts();
+            return result.map(|mut stream| {
+                stream.attempts = retry.attempts();
+                stream
+            });
+        }
+    }
+
+    /// Makes a single attempt to open a server stream.
+    async fn server_streaming_attempt(&mut self, req: &RequestMessage) -> Result<ResponseStream> {
+        let deadline = Deadline::new(req.timeout());
+        let pool = req.method_descriptor().parent_pool().clone();
+        let start = Instant::now();
+        let request_size = req.encoded_len();
+        if let Inner::Connect(client) = &mut self.inner {
+            let (headers, stream) = with_deadline(deadline, client.server_streaming(req)).await?;
+            return Ok(ResponseStream {
+                inner: Messages::Connect(stream),
+                headers,
+                deadline,
+                pool,
+                attempts: 1,
+                start,
+                metrics: Metrics::started(start, self.connect_time.since(start), request_size),
+            });
+        }
+        let grpc = self.grpc()?;
+        let response = with_deadline(deadline, async {
+            grpc.ready().await.map_err(Error::GrpcNotReady)?;
+            let codec = codec::DynamicCodec::new(req.method_descriptor());
+            let path = req.path();
+            let request = req.clone().into_request();
+            let response = grpc
+                .server_streaming(request, path, codec)
+                .await
+                .map_err(|status| grpc_error(&status, &pool))?;
+            Ok(response)
+        })
+        .await?;
+        let metrics = Metrics::started(start, self.connect_time.since(start), request_size);
+        Ok(ResponseStream::new(
+            response, deadline, pool, start, metrics,
+        ))
+    }
+
+    /// Make a client streaming `gRPC` call from the client. The messages
+    /// are sent in order. The metadata of the first message is used for
+    /// the call. The response holds the headers and trailers sent by the
+    /// server. Failed calls are retried with all messages as defined by
+    /// the service config.
+    ///
+    /// # Errors
+    /// - No request message is given
+    /// - The transport does not support client streaming
+    /// - `gRPC` client is not ready
+    /// - Server call failed
+    pub async fn client_streaming(&mut self, reqs: Vec<RequestMessage>) -> Result<ResponseMessage> {
+        self.check_client_streaming()?;
+        let Some(first) = reqs.first().cloned() else {
+            return Err(Error::Internal(String::from("No request messages")));
+        };
+        let mut retry = self.retry(&first);
+        loop {
+            let mut attempt = reqs.clone();
+            attempt[0] = retry.request(&first);
+            let result = self.client_streaming_attempt(attempt).await;
+            if let Err(err) = &result {
+                if retry.retry(err).await {
+                    continue;
+                }
+            }
+            self.attempts = retry.attempts();
+            return result.map(|mut response| {
+                response.set_attempts(retry.attempts());
+                response
+            });
+        }
+    }
+
+    /// Makes a single attempt of a client streaming call.
+    async fn client_streaming_attempt(
+        &mut self,
+        reqs: Vec<RequestMessage>,
+    ) -> Result<ResponseMessage> {
+        let first = reqs[0].clone();
+        let codec = codec::DynamicCodec::new(first.method_descriptor());
+        let path = first.path();
+        let deadline = Deadline::new(first.timeout());
+        let pool = first.method_descriptor().parent_pool().clone();
+        let start = Instant::now();
+        let request_size = reqs.iter().map(RequestMessage::encoded_len).sum();
+        let request = streaming_request(&first, tokio_stream::iter(reqs));
+        let grpc = self.grpc()?;
+        let stream = with_deadline(deadline, async {
+            grpc.ready().await.map_err(Error::GrpcNotReady)?;
+            let response = grpc
+                .streaming(request, path, codec)
+                .await
+                .map_err(|status| grpc_error(&status, &pool))?;
+            Ok(response)
+        })
+        .await?;
+        let metrics = Metrics::started(start, self.connect_time.since(start), request_size);
+        ResponseStream::new(stream, deadline, pool, start, metrics)
+            .single_message()
+            .await
+    }
+
+    /// Opens a bidirectional streaming session. The given request is sent
+    /// as the first message, further messages are sent via the session.
+    /// The session is returned once the client is ready, before the
+    /// server sent its response headers, so that servers which wait for
+    /// the first messages of the client do not block the session.
+    ///
+    /// # Errors
+    /// - The transport does not support client streaming
+    /// - `gRPC` client is not ready
+    pub async fn bidi_streaming(&mut self, req: &RequestMessage) -> Result<StreamingSession> {
+        self.check_client_streaming()?;
+        let deadline = Deadline::new(req.timeout());
         let codec = codec::DynamicCodec::new(req.method_descriptor());
         let path = req.path();
-        let request = req.clone().into_request();
-        let response = self.grpc.unary(request, path, codec).await?.into_inner();
-        Ok(response)
+        let (sx, rx) = mpsc::unbounded_channel();
+        let sender = SessionSender(sx);
+        sender.send(req.clone())?;
+        let pool = req.method_descriptor().parent_pool().clone();
+        let start = Instant::now();
+        let request_size = req.encoded_len();
+        let request = streaming_request(req, UnboundedReceiverStream::new(rx));
+        let connect_time = self.connect_time.clone();
+        // The cloned client is owned by the response future, it must be
+        // ready before it is called.
+        let mut grpc = self.grpc()?.clone();
+        with_deadline(deadline, async {
+            grpc.ready().await.map_err(Error::GrpcNotReady)
+        })
+        .await?;
+        let response = async move {
+            let response = with_deadline(deadline, async {
+                grpc.streaming(request, path, codec)
+                    .await
+                    .map_err(|status| grpc_error(&status, &pool))
+            })
+            .await?;
+            let metrics = Metrics::started(start, connect_time.since(start), request_size);
+            Ok(ResponseStream::new(
+                response, deadline, pool, start, metrics,
+            ))
+        };
+        Ok(StreamingSession {
+            sender,
+            response: PendingResponse(Box::pin(response)),
+        })
+    }
+}
+
+/// A bidirectional streaming session. Owns the sending half of the
+/// request stream and the response stream, which opens once the
+/// server sent its headers.
+#[derive(Debug)]
+pub struct StreamingSession {
+    sender: SessionSender,
+    response: PendingResponse,
+}
+
+impl StreamingSession {
+    /// Sends a message to the server.
+    ///
+    /// # Errors
+    /// - The session is closed
+    pub fn send(&self, req: RequestMessage) -> Result<()> {
+        self.sender.send(req)
+    }
+
+    /// Splits the session into the sending half and the pending response
+    /// stream. The request stream is half-closed once all senders are
+    /// dropped.
+    #[must_use]
+    pub fn into_parts(self) -> (SessionSender, PendingResponse) {
+        (self.sender, self.response)
+    }
+}
+
+/// The response stream of a [`StreamingSession`]. Resolves once the
+/// server sent its response headers.
+pub struct PendingResponse(Pin<Box<dyn Future<Output = Result<ResponseStream>> + Send>>);
+
+impl Future for PendingResponse {
+    type Output = Result<ResponseStream>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        self.0.as_mut().poll(cx)
+    }
+}
+
+impl std::fmt::Debug for PendingResponse {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PendingResponse").finish_non_exhaustive()
+    }
+}
+
+/// The sending half of a [`StreamingSession`].
+#[derive(Debug, Clone)]
+pub struct SessionSender(mpsc::UnboundedSender<RequestMessage>);
+
+impl SessionSender {
+    /// Queues a message to be sent to the server. Does not block.
+    ///
+    /// # Errors
+    /// - The session is closed
+    pub fn send(&self, req: RequestMessage) -> Result<()> {
+        self.0
+            .send(req)
+            .map_err(|_| Error::Internal(String::from("Session is closed")))
+    }
+}
+
+/// Returns the path of the socket if the address is of the
+/// form `unix:///path/to/socket`.
+#[must_use]
+pub fn unix_socket_path(address: &str) -> Option<&str> {
+    address
+        .strip_prefix(UNIX_SCHEME)
+        .filter(|path| !path.is_empty())
+}
+
+/// The scheme of Unix domain socket addresses.
+const UNIX_SCHEME: &str = "unix://";
+
+/// Returns the origin of the requests, i.e. the uri with its
+/// authority replaced by the given authority.
+fn origin(uri: &Uri, authority: &str) -> Result<Uri> {
+    let scheme = uri.scheme().cloned().unwrap_or(Scheme::HTTP);
+    Uri::builder()
+        .scheme(scheme)
+        .authority(authority)
+        .path_and_query("/")
+        .build()
+        .map_err(|_| Error::InvalidAuthority(authority.to_string()))
+}
+
+/// Wraps a stream of messages in a `tonic::Request` with the metadata
+/// and the timeout of the given request.
+fn streaming_request<S>(req: &RequestMessage, stream: S) -> Request<S> {
+    let mut request = Request::new(stream);
+    if let Some(meta) = req.metadata() {
+        *request.metadata_mut() = meta.inner.clone();
+    }
+    if let Some(timeout) = req.timeout() {
+        request.set_timeout(timeout);
+    }
+    request
+}
+
+/// The point in time at which a call is cancelled by the client.
+#[derive(Debug, Clone, Copy)]
+struct Deadline {
+    /// The timeout of the call
+    timeout: Duration,
+    /// The point in time at which the timeout elapses
+    instant: Instant,
+}
+
+impl Deadline {
+    /// Returns the deadline of a call that starts now.
+    fn new(timeout: Option<Duration>) -> Option<Self> {
+        timeout.map(|timeout| Self {
+            timeout,
+            instant: Instant::now() + timeout,
+        })
+    }
+}
+
+/// Awaits the future, fails with `DeadlineExceeded` if the deadline
+/// elapses first.
+async fn with_deadline<T>(
+    deadline: Option<Deadline>,
+    future: impl Future<Output = Result<T>>,
+) -> Result<T> {
+    let Some(deadline) = deadline else {
+        return future.await;
+    };
+    match tokio::time::timeout_at(deadline.instant, future).await {
+        // Tonic enforces the `grpc-timeout` header as well, but reports
+        // an elapsed timeout as cancelled.
+        Ok(Err(Error::GrpcError(status)))
+            if status.code == Code::Cancelled && Instant::now() >= deadline.instant =>
+        {
+            Err(Error::DeadlineExceeded(deadline.timeout))
+        }
+        Ok(result) => result,
+        Err(_) => Err(Error::DeadlineExceeded(deadline.timeout)),
+    }
+}
+
+/// Converts the status of a failed call into an error. The rich error
+/// details are decoded with the descriptor pool of the called method.
+fn grpc_error(status: &tonic::Status, pool: &DescriptorPool) -> Error {
+    Error::from(GrpcStatus::from_status(status, Some(pool)))
+}
+
+/// A stream of response messages returned by a streaming `gRPC` call.
+#[derive(Debug)]
+pub struct ResponseStream {
+    inner: Messages,
+    headers: Metadata,
+    deadline: Option<Deadline>,
+    /// The descriptor pool of the called method, used to decode
+    /// the rich error details of a failed call.
+    pool: DescriptorPool,
+    /// The number of attempts to open the stream.
+    attempts: u32,
+    /// The start of the call.
+    start: Instant,
+    /// The timings and message sizes of the messages received so far.
+    metrics: Metrics,
+}
+
+/// The response messages of a streaming call.
+#[derive(Debug)]
+enum Messages {
+    /// The messages of a `gRPC` or `gRPC-Web` call.
+    Grpc(Streaming<ResponseMessage>),
+    /// The messages of a Connect call.
+    Connect(ConnectStream),
+}
+
+impl ResponseStream {
+    fn new(
+        response: Response<Streaming<ResponseMessage>>,
+        deadline: Option<Deadline>,
+        pool: DescriptorPool,
+        start: Instant,
+        metrics: Metrics,
+    ) -> Self {
+        let (headers, inner, _) = response.into_parts();
+        Self {
+            inner: Messages::Grpc(inner),
+            headers: headers.into(),
+            deadline,
+            pool,
+            attempts: 1,
+            start,
+            metrics,
+        }
+    }
+
+    /// Get the initial metadata sent by the server.
+    #[must_use]
+    pub fn headers(&self) -> &Metadata {
+        &self.headers
+    }
+
+    /// Returns the number of attempts to open the stream, including
+    /// the retries.
+    #[must_use]
+    pub fn attempts(&self) -> u32 {
+        self.attempts
+    }
+
+    /// Returns the timings and message sizes of the messages
+    /// received so far.
+    #[must_use]
+    pub fn metrics(&self) -> &Metrics {
+        &self.metrics
+    }
+
+    /// Waits for the next message of the stream. Returns `None`
+    /// if the server closed the stream. The message holds the
+    /// metrics of the stream up to and including the message.
+    ///
+    /// # Errors
+    /// - Server sent an error status
+    /// - The deadline of the call exceeded
+    pub async fn message(&mut self) -> Result<Option<ResponseMessage>> {
+        let message = with_deadline(self.deadline, async {
+            match &mut self.inner {
+                Messages::Grpc(inner) => inner
+                    .message()
+                    .await
+                    .map_err(|status| grpc_error(&status, &self.pool)),
+                Messages::Connect(inner) => inner.message().await,
+            }
+        })
+        .await
+        .map_err(|err| err.with_headers(&self.headers))?;
+        Ok(message.map(|mut message| {
+            self.metrics.response_size += message.encoded_len();
+            self.metrics.total = self.start.elapsed();
+            message.set_metrics(self.metrics);
+            message
+        }))
+    }
+
+    /// Waits for the trailing metadata sent by the server. Drains the
+    /// remaining messages of the stream.
+    ///
+    /// # Errors
+    /// - Server sent an error status
+    /// - The deadline of the call exceeded
+    pub async fn trailers(&mut self) -> Result<Metadata> {
+        let trailers = with_deadline(self.deadline, async {
+            match &mut self.inner {
+                Messages::Grpc(inner) => {
+                    let trailers = inner
+                        .trailers()
+                        .await
+                        .map_err(|status| grpc_error(&status, &self.pool))?;
+                    Ok(trailers.map(Metadata::from).unwrap_or_default())
+                }
+                Messages::Connect(inner) => inner.trailers().await,
+            }
+        })
+        .await
+        .map_err(|err| err.with_headers(&self.headers))?;
+        self.metrics.total = self.start.elapsed();
+        Ok(trailers)
+    }
+
+    /// Reads the single message of a unary response together with
+    /// the headers and trailers. Like `gRPC`, fails with `Internal`
+    /// if the server sent more than one message.
+    async fn single_message(mut self) -> Result<ResponseMessage> {
+        let mut message = self
+            .message()
+            .await?
+            .ok_or_else(|| Error::Internal(String::from("Missing response message")))?;
+        if self.message().await?.is_some() {
+            return Err(Error::from(GrpcStatus {
+                headers: self.headers,
+                ..GrpcStatus::new(
+                    Code::Internal,
+                    "expected one response message, received more",
+                )
+            }));
+        }
+        let trailers = self.trailers().await?;
+        message.set_headers(self.headers);
+        message.set_trailers(trailers);
+        message.set_metrics(self.metrics);
+        Ok(message)
     }
 }
 
@@ -57,12 +835,14 @@ impl GrpcClient {
 ///
 /// # Errors
 /// - Internal error calling the `gRPC` server
-pub fn call_unary_blocking(req: &RequestMessage) -> Result<ResponseMessage> {
+pub fn call_unary_blocking(
+    req: &RequestMessage,
+    connection: Option<ConnectionConfig>,
+) -> Result<ResponseMessage> {
     let rt = create_runtime()?;
-    let uri = Uri::try_from(req.address())
-        .map_err(|_| Error::Internal(String::from("Failed to parse address")))?;
     let future = async move {
-        let mut client = GrpcClient::new(uri, None);
+        let mut client = GrpcClient::from_address(req.address(), connection, req.transport())?
+            .with_compression(req.compression());
         let response = client.unary(req).await?;
         Ok(response)
     };
@@ -79,13 +859,60 @@ pub fn call_unary_blocking(req: &RequestMessage) -> Result<ResponseMessage> {
 ///
 /// # Errors
 /// - Internal error calling the `gRPC` server
-pub async fn call_unary_async(req: &RequestMessage) -> Result<ResponseMessage> {
-    let uri = Uri::try_from(req.address())
-        .map_err(|_| Error::Internal(String::from("Failed to parse address")))?;
-    let mut client = GrpcClient::new(uri, None);
+pub async fn call_unary_async(
+    req: &RequestMessage,
+    connection: Option<ConnectionConfig>,
+) -> Result<ResponseMessage> {
+    let mut client = GrpcClient::from_address(req.address(), connection, req.transport())?
+        .with_compression(req.compression());
     client.unary(req).await
 }
 
+/// Creates a new `gRPC` client and opens a server stream.
+/// This method is async.
+///
+/// # Errors
+/// - Internal error calling the `gRPC` server
+pub async fn call_server_streaming_async(
+    req: &RequestMessage,
+    connection: Option<ConnectionConfig>,
+) -> Result<ResponseStream> {
+    let mut client = GrpcClient::from_address(req.address(), connection, req.transport())?
+        .with_compression(req.compression());
+    client.server_streaming(req).await
+}
+
+/// Creates a new `gRPC` client and streams messages to a `gRPC` server.
+/// This method is async.
+///
+/// # Errors
+/// - Internal error calling the `gRPC` server
+pub async fn call_client_streaming_async(
+    reqs: Vec<RequestMessage>,
+    connection: Option<ConnectionConfig>,
+) -> Result<ResponseMessage> {
+    let Some(first) = reqs.first() else {
+        return Err(Error::Internal(String::from("No request messages")));
+    };
+    let mut client = GrpcClient::from_address(first.address(), connection, first.transport())?
+        .with_compression(first.compression());
+    client.client_streaming(reqs).await
+}
+
+/// Creates a new `gRPC` client and opens a bidirectional streaming session.
+/// This method is async.
+///
+/// # Errors
+/// - Internal error calling the `gRPC` server
+pub async fn call_bidi_streaming_async(
+    req: &RequestMessage,
+    connection: Option<ConnectionConfig>,
+) -> Result<StreamingSession> {
+    let mut client = GrpcClient::from_address(req.address(), connection, req.transport())?
+        .with_compression(req.compression());
+    client.bidi_streaming(req).await
+}
+
 /// Creates a new Tokio runtime.
 ///
 /// # Errors
@@ -93,3 +920,970 @@ pub async fn call_unary_async(req: &RequestMessage) -> Result<ResponseMessage> {
 pub fn create_runtime() -> Result<Runtime> {
     Runtime::new().map_err(|_| Error::Internal(String::from("Failed to create runtime")))
 }
+
+#[cfg(test)]
+pub(crate) mod test {
+    use super::*;
+    use crate::descriptor::status::RpcStatus;
+    use crate::ProtoDescriptor;
+    use base64::{engine::general_purpose::STANDARD_NO_PAD, Engine};
+    use endpoint::EndpointConfig;
+    use hyper::service::{make_service_fn, service_fn as hyper_service_fn};
+    use hyper::{Body, HeaderMap};
+    use prost::Message;
+    use prost_reflect::{DynamicMessage, Value};
+    use proxy::ProxyConfig;
+    use std::convert::Infallible;
+    use std::net::SocketAddr;
+    use tokio::net::TcpListener;
+    use tokio_stream::wrappers::TcpListenerStream;
+    use tokio_stream::StreamExt;
+
+    fn load_test_request(method: &str) -> RequestMessage {
+        let desc = ProtoDescriptor::new(vec!["."], vec!["test_files/test.proto"]).unwrap();
+        let method = desc
+            .get_method_by_name("proto.TestService", method)
+            .unwrap();
+        RequestMessage::new(method.input(), method)
+    }
+
+    /// Serves the handler via HTTP/2 on a local port.
+    async fn serve<F, R, B>(handler: F) -> SocketAddr
+    where
+        F: Fn(http::Request<Body>) -> R + Clone + Send + Sync + 'static,
+        R: Future<Output = http::Response<B>> + Send + 'static,
+        B: http_body::Body + Send + 'static,
+        B::Data: Send,
+        B::Error: Into<Box<dyn std::error::Error + Send + Sync>>,
+    {
+        let make_svc = make_service_fn(move |_| {
+            let handler = handler.clone();
+            async move {
+                Ok::<_, Infallible>(hyper_service_fn(move |req| {
+                    let response = handler(req);
+                    async move { Ok::<_, Infallible>(response.await) }
+                }))
+            }
+        });
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = hyper::Server::from_tcp(listener)
+            .unwrap()
+            .http2_only(true)
+            .serve(make_svc);
+        tokio::spawn(server);
+        addr
+    }
+
+    /// Serves an empty message with custom headers and trailers to every request.
+    async fn serve_with_metadata() -> SocketAddr {
+        serve_messages_with_status(1, "0").await
+    }
+
+    /// Serves the number of empty messages with custom headers and
+    /// trailers, which hold the given status, to every request.
+    async fn serve_messages_with_status(messages: usize, status: &'static str) -> SocketAddr {
+        serve(move |_| async move {
+            let (mut sender, body) = Body::channel();
+            tokio::spawn(async move {
+                for _ in 0..messages {
+                    let _ = sender.send_data(vec![0, 0, 0, 0, 0].into()).await;
+                }
+                let mut trailers = HeaderMap::new();
+                trailers.insert("grpc-status", status.parse().unwrap());
+                trailers.insert("server-timing", "db;dur=53".parse().unwrap());
+                let _ = sender.send_trailers(trailers).await;
+            });
+            http::Response::builder()
+                .header("content-type", "application/grpc")
+                .header("x-request-id", "42")
+                .body(body)
+                .unwrap()
+        })
+        .await
+    }
+
+    #[tokio::test]
+    async fn test_unary_metadata() {
+        // given
+        let addr = serve_with_metadata().await;
+        let mut req = load_test_request("Simple");
+        req.set_address(&format!("http://{addr}"));
+
+        // when
+        let resp = call_unary_async(&req, None).await.unwrap();
+
+        // then
+        assert_eq!(resp.headers().get("x-request-id").unwrap(), "42");
+        assert_eq!(resp.trailers().get("server-timing").unwrap(), "db;dur=53");
+    }
+
+    #[tokio::test]
+    async fn test_unary_error_metadata() {
+        // given
+        let addr = serve_messages_with_status(0, "5").await;
+        let mut req = load_test_request("Simple");
+        req.set_address(&format!("http://{addr}"));
+
+        // when
+        let result = call_unary_async(&req, None).await;
+
+        // then
+        let Err(Error::GrpcError(status)) = result else {
+            panic!("expected grpc error, got {result:?}");
+        };
+        assert_eq!(status.code, Code::NotFound);
+        assert_eq!(status.headers.get("x-request-id").unwrap(), "42");
+        assert_eq!(status.trailers.get("server-timing").unwrap(), "db;dur=53");
+    }
+
+    #[tokio::test]
+    async fn test_unary_rejects_extra_messages() {
+        // given
+        let addr = serve_messages_with_status(2, "0").await;
+        let mut req = load_test_request("Simple");
+        req.set_address(&format!("http://{addr}"));
+
+        // when
+        let result = call_unary_async(&req, None).await;
+
+        // then
+        let Err(Error::GrpcError(status)) = result else {
+            panic!("expected grpc error, got {result:?}");
+        };
+        assert_eq!(status.code, Code::Internal);
+        assert_eq!(status.headers.get("x-request-id").unwrap(), "42");
+    }
+
+    /// Echoes the request body of every request as response.
+    async fn serve_echo() -> SocketAddr {
+        serve(|req: http::Request<Body>| async {
+            let data = hyper::body::to_bytes(req.into_body()).await.unwrap();
+            let (mut sender, body) = Body::channel();
+            tokio::spawn(async move {
+                let _ = sender.send_data(data).await;
+                let mut trailers = HeaderMap::new();
+                trailers.insert("grpc-status", "0".parse().unwrap());
+                let _ = sender.send_trailers(trailers).await;
+            });
+            http::Response::builder()
+                .header("content-type", "application/grpc")
+                .body(body)
+                .unwrap()
+        })
+        .await
+    }
+
+    #[tokio::test]
+    async fn test_unary_raw_payload() {
+        // given
+        let addr = serve_echo().await;
+        let mut req = load_test_request("Simple");
+        req.set_address(&format!("http://{addr}"));
+        // number = 5 and the unknown field 99 = 1
+        let payload = bytes::Bytes::from_static(&[0x08, 0x05, 0x98, 0x06, 0x01]);
+        req.set_raw(Some(payload.clone()));
+
+        // when
+        let resp = call_unary_async(&req, None).await.unwrap();
+
+        // then
+        assert_eq!(resp.raw(), &payload);
+        assert_eq!(resp.message.to_json().unwrap(), "{}");
+    }
+
+    #[tokio::test]
+    async fn test_unary_metrics() {
+        // given
+        let addr = serve_echo().await;
+        let mut req = load_test_request("Simple");
+        req.set_address(&format!("http://{addr}"));
+        req.set_raw(Some(bytes::Bytes::from_static(&[0x08, 0x05])));
+        let mut client = GrpcClient::from_address(req.address(), None, Transport::Grpc).unwrap();
+
+        // when
+        let first = client.unary(&req).await.unwrap();
+        let second = client.unary(&req).await.unwrap();
+
+        // then
+        let metrics = first.metrics();
+        assert!(metrics.connect.is_some());
+        assert!(metrics.connect <= metrics.first_byte);
+        assert!(metrics.first_byte <= Some(metrics.total));
+        assert_eq!(metrics.request_size, 2);
+        assert_eq!(metrics.response_size, 2);
+        assert_eq!(second.metrics().connect, None);
+    }
+
+    /// Serves a HTTP CONNECT proxy which requires the given basic auth
+    /// header. Returns the address and the number of tunnels.
+    async fn serve_connect_proxy(
+        authorization: &'static str,
+    ) -> (SocketAddr, Arc<std::sync::atomic::AtomicUsize>) {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+        let tunnels = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let count = Arc::clone(&tunnels);
+        tokio::spawn(async move {
+            while let Ok((mut stream, _)) = listener.accept().await {
+                let count = Arc::clone(&count);
+                tokio::spawn(async move {
+                    let mut head = Vec::new();
+                    let mut buf = [0; 1024];
+                    while !head.windows(4).any(|w| w == b"\r\n\r\n") {
+                        let n = stream.read(&mut buf).await.unwrap();
+                        head.extend_from_slice(&buf[..n]);
+                    }
+                    let head = String::from_utf8(head).unwrap();
+                    let target = head.split_whitespace().nth(1).unwrap().to_string();
+                    let expected = format!("proxy-authorization: {authorization}");
+                    if !head
+                        .to_ascii_lowercase()
+                        .contains(&expected.to_ascii_lowercase())
+                    {
+                        let _ = stream
+                            .write_all(b"HTTP/1.1 407 Proxy Authentication Required\r\n\r\n")
+                            .await;
+                        return;
+                    }
+                    count.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+                    let mut upstream = tokio::net::TcpStream::connect(target).await.unwrap();
+                    stream
+                        .write_all(b"HTTP/1.1 200 Connection established\r\n\r\n")
+                        .await
+                        .unwrap();
+                    let _ = tokio::io::copy_bidirectional(&mut stream, &mut upstream).await;
+                });
+            }
+        });
+        (addr, tunnels)
+    }
+
+    #[tokio::test]
+    async fn test_unary_proxy() {
+        // given
+        let addr = serve_with_metadata().await;
+        let (proxy_addr, tunnels) = serve_connect_proxy("Basic dXNlcjpzZWNyZXQ=").await;
+        let proxy = ProxyConfig::new(Some(format!("http://user:secret@{proxy_addr}")));
+        let connection = ConnectionConfig::default().with_proxy(proxy);
+        let mut req = load_test_request("Simple");
+        req.set_address(&format!("http://{addr}"));
+
+        // when
+        let resp = call_unary_async(&req, Some(connection)).await.unwrap();
+
+        // then
+        assert_eq!(resp.headers().get("x-request-id").unwrap(), "42");
+        assert_eq!(tunnels.load(std::sync::atomic::Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_unary_proxy_unauthorized() {
+        // given
+        let addr = serve_with_metadata().await;
+        let (proxy_addr, tunnels) = serve_connect_proxy("Basic dXNlcjpzZWNyZXQ=").await;
+        let proxy = ProxyConfig::new(Some(format!("http://user:wrong@{proxy_addr}")));
+        let connection = ConnectionConfig::default().with_proxy(proxy);
+        let mut req = load_test_request("Simple");
+        req.set_address(&format!("http://{addr}"));
+
+        // when
+        let result = call_unary_async(&req, Some(connection)).await;
+
+        // then
+        assert!(result.is_err());
+        assert_eq!(tunnels.load(std::sync::atomic::Ordering::SeqCst), 0);
+    }
+
+    /// Responds with an `Unavailable` status to the given number of
+    /// requests, then with an empty message.
+    async fn serve_unavailable(failures: usize) -> SocketAddr {
+        let count = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        serve(move |_| {
+            let n = count.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            let response = http::Response::builder().header("content-type", "application/grpc");
+            let response = if n < failures {
+                response.header("grpc-status", "14").body(Body::empty())
+            } else {
+                response
+                    .header("grpc-status", "0")
+                    .body(Body::from(vec![0, 0, 0, 0, 0]))
+            };
+            async { response.unwrap() }
+        })
+        .await
+    }
+
+    fn retry_config(max_attempts: u32) -> Arc<ServiceConfig> {
+        let json = format!(
+            r#"{{"methodConfig": [{{
+                "name": [{{"service": "proto.TestService"}}],
+                "retryPolicy": {{
+                    "maxAttempts": {max_attempts},
+                    "initialBackoff": "0.01s",
+                    "maxBackoff": "0.1s",
+                    "backoffMultiplier": 2,
+                    "retryableStatusCodes": ["UNAVAILABLE"]
+                }}
+            }}]}}"#
+        );
+        Arc::new(ServiceConfig::from_json(&json).unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_unary_retry() {
+        // given
+        let addr = serve_unavailable(2).await;
+        let mut req = load_test_request("Simple");
+        req.set_address(&format!("http://{addr}"));
+        let mut client = GrpcClient::from_address(req.address(), None, Transport::Grpc)
+            .unwrap()
+            .with_service_config(retry_config(3));
+
+        // when
+        let resp = client.unary(&req).await.unwrap();
+
+        // then
+        assert_eq!(resp.attempts(), 3);
+        assert_eq!(client.attempts(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_unary_retry_exhausted() {
+        // given
+        let addr = serve_unavailable(5).await;
+        let mut req = load_test_request("Simple");
+        req.set_address(&format!("http://{addr}"));
+        let mut client = GrpcClient::from_address(req.address(), None, Transport::Grpc)
+            .unwrap()
+            .with_service_config(retry_config(2));
+
+        // when
+        let result = client.unary(&req).await;
+
+        // then
+        let Err(Error::GrpcError(status)) = result else {
+            panic!("expected grpc error, got {result:?}");
+        };
+        assert_eq!(status.code, Code::Unavailable);
+        assert_eq!(client.attempts(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_unary_wait_for_ready_exhausted() {
+        // given
+        let addr = serve_unavailable(usize::MAX).await;
+        let mut req = load_test_request("Simple");
+        req.set_address(&format!("http://{addr}"));
+        let json = r#"{"methodConfig": [{
+            "name": [{"service": "proto.TestService"}],
+            "waitForReady": true
+        }]}"#;
+        let mut client = GrpcClient::from_address(req.address(), None, Transport::Grpc)
+            .unwrap()
+            .with_service_config(Arc::new(ServiceConfig::from_json(json).unwrap()));
+
+        // when
+        let result = client.unary(&req).await;
+
+        // then
+        let Err(Error::GrpcError(status)) = result else {
+            panic!("expected grpc error, got {result:?}");
+        };
+        assert_eq!(status.code, Code::Unavailable);
+        assert_eq!(client.attempts(), 5);
+    }
+
+    /// Responds to every request with an `InvalidArgument` status and
+    /// the given rich error details.
+    async fn serve_with_status_details(details: Vec<u8>) -> SocketAddr {
+        let details = STANDARD_NO_PAD.encode(details);
+        serve(move |_| {
+            let response = http::Response::builder()
+                .header("content-type", "application/grpc")
+                .header("grpc-status", "3")
+                .header("grpc-message", "invalid%20number")
+                .header("grpc-status-details-bin", details.clone())
+                .body(Body::empty())
+                .unwrap();
+            async { response }
+        })
+        .await
+    }
+
+    #[tokio::test]
+    async fn test_unary_status_details() {
+        // given
+        let mut req = load_test_request("Simple");
+        let mut detail = DynamicMessage::new(req.message_descriptor());
+        detail.set_field_by_name("number", Value::I32(7));
+        let status = RpcStatus {
+            code: 3,
+            message: String::from("invalid number"),
+            details: vec![prost_types::Any {
+                type_url: String::from("type.googleapis.com/proto.SimpleReq"),
+                value: detail.encode_to_vec(),
+            }],
+        };
+        let addr = serve_with_status_details(status.encode_to_vec()).await;
+        req.set_address(&format!("http://{addr}"));
+
+        // when
+        let result = call_unary_async(&req, None).await;
+
+        // then
+        let Err(Error::GrpcError(status)) = result else {
+            panic!("expected grpc error, got {result:?}");
+        };
+        assert_eq!(status.code, Code::InvalidArgument);
+        assert_eq!(status.message, "invalid number");
+        let expected = serde_json::json!({
+            "code": 3,
+            "message": "invalid number",
+            "details": [{"@type": "type.googleapis.com/proto.SimpleReq", "number": 7}],
+        });
+        assert_eq!(status.details, Some(expected));
+    }
+
+    /// Serves the health service via `gRPC-Web`. The service `test`
+    /// is serving, all other services are unknown.
+    async fn serve_grpc_web_health() -> SocketAddr {
+        let (mut reporter, service) = tonic_health::server::health_reporter();
+        reporter
+            .set_service_status("test", tonic_health::ServingStatus::Serving)
+            .await;
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = tonic::transport::Server::builder()
+            .accept_http1(true)
+            .add_service(tonic_web::enable(service))
+            .serve_with_incoming(TcpListenerStream::new(listener));
+        tokio::spawn(server);
+        addr
+    }
+
+    fn load_health_request(service: &str) -> RequestMessage {
+        let desc =
+            ProtoDescriptor::new(vec!["protos"], vec!["grpc/health/v1/health.proto"]).unwrap();
+        let method = desc
+            .get_method_by_name("grpc.health.v1.Health", "Check")
+            .unwrap();
+        let mut req = RequestMessage::new(method.input(), method);
+        let json = format!("{{\"service\":\"{service}\"}}");
+        req.message_mut().from_json(&json).unwrap();
+        req.set_transport(Transport::GrpcWeb);
+        req
+    }
+
+    #[tokio::test]
+    async fn test_unary_grpc_web() {
+        // given
+        let addr = serve_grpc_web_health().await;
+        let mut req = load_health_request("test");
+        req.set_address(&format!("http://{addr}"));
+
+        // when
+        let resp = call_unary_async(&req, None).await.unwrap();
+
+        // then
+        assert_eq!(resp.message.to_json().unwrap(), "{\"status\":\"SERVING\"}");
+        assert_eq!(
+            resp.headers().get("content-type").unwrap(),
+            "application/grpc-web+proto"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_unary_grpc_web_error_status() {
+        // given
+        let addr = serve_grpc_web_health().await;
+        let mut req = load_health_request("unknown");
+        req.set_address(&format!("http://{addr}"));
+
+        // when
+        let result = call_unary_async(&req, None).await;
+
+        // then
+        let Err(Error::GrpcError(status)) = result else {
+            panic!("expected grpc error, got {result:?}");
+        };
+        assert_eq!(status.code, Code::NotFound);
+    }
+
+    #[tokio::test]
+    async fn test_client_streaming_grpc_web_unsupported() {
+        // given
+        let mut req = load_test_request("Simple");
+        req.set_address("http://localhost:50051");
+        req.set_transport(Transport::GrpcWeb);
+
+        // when
+        let result = call_client_streaming_async(vec![req], None).await;
+
+        // then
+        assert!(matches!(
+            result,
+            Err(Error::UnsupportedStreaming(Transport::GrpcWeb))
+        ));
+    }
+
+    /// Serves the health service which sends and accepts gzip compressed
+    /// messages. The service `test` is serving.
+    async fn serve_compressed_health() -> SocketAddr {
+        let (mut reporter, service) = tonic_health::server::health_reporter();
+        reporter
+            .set_service_status("test", tonic_health::ServingStatus::Serving)
+            .await;
+        let service = service
+            .send_compressed(tonic::codec::CompressionEncoding::Gzip)
+            .accept_compressed(tonic::codec::CompressionEncoding::Gzip);
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = tonic::transport::Server::builder()
+            .add_service(service)
+            .serve_with_incoming(TcpListenerStream::new(listener));
+        tokio::spawn(server);
+        addr
+    }
+
+    #[tokio::test]
+    async fn test_unary_compressed() {
+        // given
+        let addr = serve_compressed_health().await;
+        let mut req = load_health_request("test");
+        req.set_address(&format!("http://{addr}"));
+        req.set_transport(Transport::Grpc);
+        req.set_compression(Compression {
+            send_gzip: true,
+            accept_gzip: true,
+        });
+
+        // when
+        let resp = call_unary_async(&req, None).await.unwrap();
+
+        // then
+        assert_eq!(resp.message.to_json().unwrap(), "{\"status\":\"SERVING\"}");
+        assert_eq!(resp.headers().encoding(), Some("gzip"));
+    }
+
+    #[tokio::test]
+    async fn test_unary_uncompressed() {
+        // given
+        let addr = serve_compressed_health().await;
+        let mut req = load_health_request("test");
+        req.set_address(&format!("http://{addr}"));
+        req.set_transport(Transport::Grpc);
+
+        // when
+        let resp = call_unary_async(&req, None).await.unwrap();
+
+        // then
+        assert_eq!(resp.headers().encoding(), None);
+    }
+
+    #[tokio::test]
+    async fn test_unary_max_decoding_message_size() {
+        // given
+        let addr = serve_compressed_health().await;
+        let mut req = load_health_request("test");
+        req.set_address(&format!("http://{addr}"));
+        req.set_transport(Transport::Grpc);
+        let endpoint = EndpointConfig {
+            max_decoding_message_size: Some(1),
+            ..Default::default()
+        };
+        let connection = ConnectionConfig::default().with_endpoint(endpoint);
+
+        // when
+        let result = call_unary_async(&req, Some(connection)).await;
+
+        // then
+        let Err(Error::GrpcError(status)) = result else {
+            panic!("expected an error, got {result:?}");
+        };
+        assert_eq!(status.code, Code::OutOfRange);
+    }
+
+    /// Serves the `Check` and `Watch` methods of the health service via
+    /// the Connect protocol. The service `test` is serving, all other
+    /// services are unknown.
+    async fn serve_connect_health() -> SocketAddr {
+        let make_svc = make_service_fn(|_| async {
+            Ok::<_, Infallible>(hyper_service_fn(
+                |request: http::Request<Body>| async move {
+                    let path = request.uri().path().to_string();
+                    let content_type = request.headers()["content-type"].clone();
+                    let body = hyper::body::to_bytes(request.into_body()).await.unwrap();
+                    let response = http::Response::builder().header("trailer-x-served-by", "test");
+                    let response = match content_type.to_str().unwrap() {
+                        "application/proto" => response.body(Body::from(vec![8, 1])),
+                        "application/json" if body.as_ref() == br#"{"service":"test"}"# => {
+                            response.body(Body::from(r#"{"status":"SERVING"}"#))
+                        }
+                        "application/json" => response
+                            .status(404)
+                            .body(Body::from(r#"{"code":"not_found","message":"unknown"}"#)),
+                        _ => {
+                            assert!(path.ends_with("/Watch"));
+                            let mut body = connect::envelope(0, br#"{"status":"SERVING"}"#);
+                            body.extend(connect::envelope(
+                                2,
+                                br#"{"metadata":{"x-served-by":["test"]}}"#,
+                            ));
+                            response.body(Body::from(body))
+                        }
+                    };
+                    Ok::<_, Infallible>(response.unwrap())
+                },
+            ))
+        });
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = hyper::Server::from_tcp(listener).unwrap().serve(make_svc);
+        tokio::spawn(server);
+        addr
+    }
+
+    #[tokio::test]
+    async fn test_unary_connect() {
+        // given
+        let addr = serve_connect_health().await;
+        let mut req = load_health_request("test");
+        req.set_address(&format!("http://{addr}"));
+        req.set_transport(Transport::Connect);
+
+        // when
+        let resp = call_unary_async(&req, None).await.unwrap();
+
+        // then
+        assert_eq!(resp.message.to_json().unwrap(), "{\"status\":\"SERVING\"}");
+        assert_eq!(resp.trailers().get("x-served-by").unwrap(), "test");
+        assert!(resp.headers().get("trailer-x-served-by").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_unary_connect_json_error() {
+        // given
+        let addr = serve_connect_health().await;
+        let mut req = load_health_request("unknown");
+        req.set_address(&format!("http://{addr}"));
+        req.set_transport(Transport::ConnectJson);
+
+        // when
+        let result = call_unary_async(&req, None).await;
+
+        // then
+        let Err(Error::GrpcError(status)) = result else {
+            panic!("expected grpc error, got {result:?}");
+        };
+        assert_eq!(status.code, Code::NotFound);
+        assert_eq!(status.message, "unknown");
+    }
+
+    #[tokio::test]
+    async fn test_server_streaming_connect_json() {
+        // given
+        let addr = serve_connect_health().await;
+        let desc =
+            ProtoDescriptor::new(vec!["protos"], vec!["grpc/health/v1/health.proto"]).unwrap();
+        let method = desc
+            .get_method_by_name("grpc.health.v1.Health", "Watch")
+            .unwrap();
+        let mut req = RequestMessage::new(method.input(), method);
+        req.set_address(&format!("http://{addr}"));
+        req.set_transport(Transport::ConnectJson);
+
+        // when
+        let mut stream = call_server_streaming_async(&req, None).await.unwrap();
+
+        // then
+        let message = stream.message().await.unwrap().unwrap();
+        assert_eq!(
+            message.message.to_json().unwrap(),
+            "{\"status\":\"SERVING\"}"
+        );
+        assert!(stream.message().await.unwrap().is_none());
+        let trailers = stream.trailers().await.unwrap();
+        assert_eq!(trailers.get("x-served-by").unwrap(), "test");
+    }
+
+    #[tokio::test]
+    async fn test_server_streaming_connect_max_decoding_message_size() {
+        // given
+        let addr = serve_connect_health().await;
+        let desc =
+            ProtoDescriptor::new(vec!["protos"], vec!["grpc/health/v1/health.proto"]).unwrap();
+        let method = desc
+            .get_method_by_name("grpc.health.v1.Health", "Watch")
+            .unwrap();
+        let mut req = RequestMessage::new(method.input(), method);
+        req.set_address(&format!("http://{addr}"));
+        req.set_transport(Transport::ConnectJson);
+        let endpoint = EndpointConfig {
+            max_decoding_message_size: Some(8),
+            ..Default::default()
+        };
+        let connection = ConnectionConfig::default().with_endpoint(endpoint);
+
+        // when
+        let mut stream = call_server_streaming_async(&req, Some(connection))
+            .await
+            .unwrap();
+        let result = stream.message().await;
+
+        // then
+        let Err(Error::GrpcError(status)) = result else {
+            panic!("expected an error, got {result:?}");
+        };
+        assert_eq!(status.code, Code::OutOfRange);
+    }
+
+    pub(crate) fn load_echo_request(method: &str, text: &str) -> RequestMessage {
+        let desc = ProtoDescriptor::new(vec!["."], vec!["test_files/echo.proto"]).unwrap();
+        let method = desc.get_method_by_name("echo.Echo", method).unwrap();
+        let mut req = RequestMessage::new(method.input(), method);
+        req.message_mut()
+            .from_json(&format!("{{\"text\": \"{text}\"}}"))
+            .unwrap();
+        req
+    }
+
+    /// Returns a copy of an echo request with another text.
+    pub(crate) fn with_text(req: &RequestMessage, text: &str) -> RequestMessage {
+        let mut req = req.clone();
+        req.message_mut()
+            .set_field_by_name("text", Value::String(text.to_string()));
+        req
+    }
+
+    /// Returns the text of an echoed message.
+    fn echo_text(resp: &ResponseMessage) -> String {
+        let text = resp.message.get_field_by_name("text").unwrap();
+        text.as_str().unwrap().to_string()
+    }
+
+    /// Returns a response with the message of the request.
+    fn echo(req: &RequestMessage, text: &str) -> ResponseMessage {
+        let method = req.method_descriptor();
+        let mut resp = ResponseMessage::new(method.output(), method.clone());
+        resp.message
+            .set_field_by_name("text", Value::String(text.to_string()));
+        resp
+    }
+
+    /// Serves the `echo.Echo` service. Server streams echo the message
+    /// three times, client streams respond with the joined messages.
+    /// `BidiStream` echoes every message as it arrives, `BidiCollect`
+    /// echoes all messages once the client half-closed the stream.
+    /// Reports whether the request stream of `BidiStream` ended with
+    /// a half-close (`true`) or an error (`false`).
+    pub(crate) async fn serve_echo_streams() -> (SocketAddr, mpsc::UnboundedReceiver<bool>) {
+        let (closed_sx, closed_rx) = mpsc::unbounded_channel();
+        let addr = serve(move |req| handle_echo(req, closed_sx.clone())).await;
+        (addr, closed_rx)
+    }
+
+    async fn handle_echo(
+        req: http::Request<Body>,
+        closed: mpsc::UnboundedSender<bool>,
+    ) -> http::Response<tonic::body::BoxBody> {
+        let desc = ProtoDescriptor::new(vec!["."], vec!["test_files/echo.proto"]).unwrap();
+        let name = req.uri().path().rsplit('/').next().unwrap().to_string();
+        let method = desc.get_method_by_name("echo.Echo", &name).unwrap();
+        let mut grpc = tonic::server::Grpc::new(codec::ServerCodec::new(method));
+        match name.as_str() {
+            "ServerStream" => {
+                let service = service_fn(|req: Request<RequestMessage>| async move {
+                    let text = echo_text_of(req.get_ref());
+                    let messages = vec![echo(req.get_ref(), &text); 3].into_iter();
+                    Ok::<_, tonic::Status>(Response::new(tokio_stream::iter(messages.map(Ok))))
+                });
+                grpc.server_streaming(service, req).await
+            }
+            "ClientStream" => {
+                let service = service_fn(|req: Request<Streaming<RequestMessage>>| async move {
+                    let mut stream = req.into_inner();
+                    let mut texts = Vec::new();
+                    let mut last = None;
+                    while let Some(req) = stream.next().await {
+                        let req = req?;
+                        texts.push(echo_text_of(&req));
+                        last = Some(req);
+                    }
+                    let last = last.ok_or_else(|| tonic::Status::invalid_argument("empty"))?;
+                    Ok(Response::new(echo(&last, &texts.join(","))))
+                });
+                grpc.client_streaming(service, req).await
+            }
+            "BidiStream" => {
+                let service = service_fn(move |req: Request<Streaming<RequestMessage>>| {
+                    let closed = closed.clone();
+                    async move {
+                        let (sx, rx) = mpsc::channel(1);
+                        tokio::spawn(async move {
+                            let mut stream = req.into_inner();
+                            loop {
+                                match stream.next().await {
+                                    Some(Ok(req)) => {
+                                        let resp = echo(&req, &echo_text_of(&req));
+                                        let _ = sx.send(Ok(resp)).await;
+                                    }
+                                    Some(Err(_)) => break closed.send(false),
+                                    None => break closed.send(true),
+                                }
+                            }
+                        });
+                        Ok::<_, tonic::Status>(Response::new(
+                            tokio_stream::wrappers::ReceiverStream::new(rx),
+                        ))
+                    }
+                });
+                grpc.streaming(service, req).await
+            }
+            _ => {
+                // Waits for the half-close before sending the headers
+                let service = service_fn(|req: Request<Streaming<RequestMessage>>| async move {
+                    let mut stream = req.into_inner();
+                    let mut messages = Vec::new();
+                    while let Some(req) = stream.next().await {
+                        let req = req?;
+                        messages.push(Ok(echo(&req, &echo_text_of(&req))));
+                    }
+                    Ok(Response::new(tokio_stream::iter(messages)))
+                });
+                grpc.streaming(service, req).await
+            }
+        }
+    }
+
+    /// Returns the text of a request message.
+    fn echo_text_of(req: &RequestMessage) -> String {
+        let text = req.message().get_field_by_name("text").unwrap();
+        text.as_str().unwrap().to_string()
+    }
+
+    #[tokio::test]
+    async fn test_server_streaming() {
+        // given
+        let (addr, _) = serve_echo_streams().await;
+        let mut req = load_echo_request("ServerStream", "hello");
+        req.set_address(&format!("http://{addr}"));
+
+        // when
+        let mut stream = call_server_streaming_async(&req, None).await.unwrap();
+        let mut texts = Vec::new();
+        while let Some(resp) = stream.message().await.unwrap() {
+            texts.push(echo_text(&resp));
+        }
+
+        // then
+        assert_eq!(texts, vec!["hello", "hello", "hello"]);
+    }
+
+    #[tokio::test]
+    async fn test_client_streaming() {
+        // given
+        let (addr, _) = serve_echo_streams().await;
+        let mut req = load_echo_request("ClientStream", "a");
+        req.set_address(&format!("http://{addr}"));
+        let reqs = vec![req.clone(), with_text(&req, "b"), with_text(&req, "c")];
+
+        // when
+        let resp = call_client_streaming_async(reqs, None).await.unwrap();
+
+        // then
+        assert_eq!(echo_text(&resp), "a,b,c");
+    }
+
+    #[tokio::test]
+    async fn test_bidi_streaming() {
+        // given
+        let (addr, mut closed) = serve_echo_streams().await;
+        let mut req = load_echo_request("BidiStream", "a");
+        req.set_address(&format!("http://{addr}"));
+
+        // when
+        let session = call_bidi_streaming_async(&req, None).await.unwrap();
+        let (sender, response) = session.into_parts();
+        let mut stream = response.await.unwrap();
+        let mut texts = vec![echo_text(&stream.message().await.unwrap().unwrap())];
+        for text in ["b", "c"] {
+            sender.send(with_text(&req, text)).unwrap();
+            texts.push(echo_text(&stream.message().await.unwrap().unwrap()));
+        }
+        drop(sender);
+        let end = stream.message().await.unwrap();
+
+        // then
+        assert_eq!(texts, vec!["a", "b", "c"]);
+        assert!(end.is_none());
+        assert_eq!(closed.recv().await, Some(true));
+    }
+
+    #[tokio::test]
+    async fn test_bidi_streaming_headers_after_half_close() {
+        // given
+        let (addr, _) = serve_echo_streams().await;
+        let mut req = load_echo_request("BidiCollect", "a");
+        req.set_address(&format!("http://{addr}"));
+        req.set_timeout(Some(Duration::from_secs(5)));
+
+        // when
+        let session = call_bidi_streaming_async(&req, None).await.unwrap();
+        session.send(with_text(&req, "b")).unwrap();
+        let (sender, response) = session.into_parts();
+        drop(sender);
+        let mut stream = response.await.unwrap();
+        let mut texts = Vec::new();
+        while let Some(resp) = stream.message().await.unwrap() {
+            texts.push(echo_text(&resp));
+        }
+
+        // then
+        assert_eq!(texts, vec!["a", "b"]);
+    }
+
+    #[tokio::test]
+    async fn test_bidi_streaming_cancel() {
+        // given
+        let (addr, mut closed) = serve_echo_streams().await;
+        let mut req = load_echo_request("BidiStream", "a");
+        req.set_address(&format!("http://{addr}"));
+        let session = call_bidi_streaming_async(&req, None).await.unwrap();
+        let (sender, response) = session.into_parts();
+        let mut stream = response.await.unwrap();
+        stream.message().await.unwrap().unwrap();
+
+        // when
+        drop(stream);
+        drop(sender);
+
+        // then the server is released
+        assert!(closed.recv().await.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_unary_deadline_exceeded() {
+        // given
+        let mut req = load_test_request("Simple");
+        req.set_timeout(Some(Duration::from_millis(50)));
+
+        // A server which accepts connections but never responds
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let mut conns = Vec::new();
+            while let Ok((conn, _)) = listener.accept().await {
+                conns.push(conn);
+            }
+        });
+        req.set_address(&format!("http://{addr}"));
+
+        // when
+        let result = call_unary_async(&req, None).await;
+
+        // then
+        assert!(matches!(result, Err(Error::DeadlineExceeded(_))));
+    }
+}
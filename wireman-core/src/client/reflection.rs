@@ -0,0 +1,207 @@
+//! Module to load file descriptors from the `gRPC` server reflection service
+use super::GrpcClient;
+use crate::error::Error;
+use crate::Result;
+use http::uri::PathAndQuery;
+use prost::Message;
+use prost_types::FileDescriptorProto;
+use std::collections::{HashMap, HashSet};
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::ReceiverStream;
+use tonic::codec::ProstCodec;
+use tonic::{Code, Request, Streaming};
+use tonic_reflection::pb::server_reflection_request::MessageRequest;
+use tonic_reflection::pb::server_reflection_response::MessageResponse;
+use tonic_reflection::pb::{ServerReflectionRequest, ServerReflectionResponse};
+
+/// The path of the `grpc.reflection.v1` reflection service.
+const REFLECTION_V1: &str = "/grpc.reflection.v1.ServerReflection/ServerReflectionInfo";
+
+/// The path of the `grpc.reflection.v1alpha` reflection service.
+const REFLECTION_V1ALPHA: &str = "/grpc.reflection.v1alpha.ServerReflection/ServerReflectionInfo";
+
+impl GrpcClient {
+    /// Loads the file descriptors of all services that are listed by the
+    /// server reflection service, including their transitive dependencies.
+    /// Tries `grpc.reflection.v1` first and falls back to `v1alpha`.
+    ///
+    /// # Errors
+    /// - `gRPC` client is not ready
+    /// - Server reflection call failed
+    /// - Failed to decode a file descriptor
+    pub async fn reflect_file_descriptors(&mut self) -> Result<Vec<FileDescriptorProto>> {
+        match self.reflect_file_descriptors_with(REFLECTION_V1).await {
+            Err(Error::GrpcError(status)) if status.code == Code::Unimplemented => {
+                self.reflect_file_descriptors_with(REFLECTION_V1ALPHA).await
+            }
+            result => result,
+        }
+    }
+
+    async fn reflect_file_descriptors_with(
+        &mut self,
+        path: &'static str,
+    ) -> Result<Vec<FileDescriptorProto>> {
+        let mut stream = self.open_reflection_stream(path).await?;
+
+        // List the services
+        let services = match stream
+            .request(MessageRequest::ListServices(String::new()))
+            .await?
+        {
+            MessageResponse::ListServicesResponse(resp) => resp.service,
+            _ => return Err(unexpected_response()),
+        };
+
+        // Resolve the files of the services and their dependencies
+        let mut pending: Vec<MessageRequest> = services
+            .into_iter()
+            .map(|service| MessageRequest::FileContainingSymbol(service.name))
+            .collect();
+        let mut requested: HashSet<String> = HashSet::new();
+        let mut files: HashMap<String, FileDescriptorProto> = HashMap::new();
+        while let Some(request) = pending.pop() {
+            let MessageResponse::FileDescriptorResponse(resp) = stream.request(request).await?
+            else {
+                return Err(unexpected_response());
+            };
+            for bytes in resp.file_descriptor_proto {
+                let file = FileDescriptorProto::decode(bytes.as_slice())
+                    .map_err(Error::DecodeFileDescriptorError)?;
+                for dependency in &file.dependency {
+                    if !files.contains_key(dependency) && requested.insert(dependency.clone()) {
+                        pending.push(MessageRequest::FileByFilename(dependency.clone()));
+                    }
+                }
+                files.entry(file.name().to_string()).or_insert(file);
+            }
+        }
+
+        Ok(files.into_values().collect())
+    }
+
+    async fn open_reflection_stream(&mut self, path: &'static str) -> Result<ReflectionStream> {
+        let grpc = self.grpc()?;
+        grpc.ready().await.map_err(Error::GrpcNotReady)?;
+        let (sx, rx) = mpsc::channel(1);
+        let request = Request::new(ReceiverStream::new(rx));
+        let codec: ProstCodec<ServerReflectionRequest, ServerReflectionResponse> =
+            ProstCodec::default();
+        let path = PathAndQuery::from_static(path);
+        let response = grpc.streaming(request, path, codec).await?.into_inner();
+        Ok(ReflectionStream {
+            sx,
+            stream: response,
+        })
+    }
+}
+
+/// A bidirectional stream to the server reflection service.
+struct ReflectionStream {
+    sx: mpsc::Sender<ServerReflectionRequest>,
+    stream: Streaming<ServerReflectionResponse>,
+}
+
+impl ReflectionStream {
+    /// Sends a reflection request and waits for the response.
+    async fn request(&mut self, message_request: MessageRequest) -> Result<MessageResponse> {
+        let request = ServerReflectionRequest {
+            host: String::new(),
+            message_request: Some(message_request),
+        };
+        self.sx
+            .send(request)
+            .await
+            .map_err(|_| Error::ReflectionError(String::from("stream closed")))?;
+        let response = self
+            .stream
+            .message()
+            .await?
+            .and_then(|resp| resp.message_response)
+            .ok_or_else(|| Error::ReflectionError(String::from("no response")))?;
+        if let MessageResponse::ErrorResponse(err) = response {
+            return Err(Error::ReflectionError(err.error_message));
+        }
+        Ok(response)
+    }
+}
+
+fn unexpected_response() -> Error {
+    Error::ReflectionError(String::from("unexpected response"))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::client::transport::Transport;
+    use crate::ProtoDescriptor;
+    use tokio::net::TcpListener;
+    use tokio_stream::wrappers::TcpListenerStream;
+    #[cfg(unix)]
+    use tokio_stream::wrappers::UnixListenerStream;
+    use tonic::transport::{Server, Uri};
+
+    #[tokio::test]
+    async fn test_from_reflection() {
+        // given
+        let file_desc_set =
+            protox::compile(["test_files/test.proto"], ["."]).expect("failed to compile");
+        let reflection = tonic_reflection::server::Builder::configure()
+            .register_file_descriptor_set(file_desc_set)
+            .build()
+            .unwrap();
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(
+            Server::builder()
+                .add_service(reflection)
+                .serve_with_incoming(TcpListenerStream::new(listener)),
+        );
+
+        // when
+        let uri = Uri::try_from(format!("http://{addr}")).unwrap();
+        let mut client = GrpcClient::new(uri, None).unwrap();
+        let files = client.reflect_file_descriptors().await.unwrap();
+        let desc = ProtoDescriptor::from_file_descriptor_protos(files).unwrap();
+
+        // then
+        assert!(desc
+            .get_method_by_name("proto.TestService", "Simple")
+            .is_some());
+        assert!(desc
+            .get_message_by_name("google.protobuf.Timestamp")
+            .is_some());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_from_reflection_unix_socket() {
+        // given
+        let file_desc_set =
+            protox::compile(["test_files/test.proto"], ["."]).expect("failed to compile");
+        let reflection = tonic_reflection::server::Builder::configure()
+            .register_file_descriptor_set(file_desc_set)
+            .build()
+            .unwrap();
+        let path = std::env::temp_dir().join(format!("wireman-{}.sock", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let listener = tokio::net::UnixListener::bind(&path).unwrap();
+        tokio::spawn(
+            Server::builder()
+                .add_service(reflection)
+                .serve_with_incoming(UnixListenerStream::new(listener)),
+        );
+
+        // when
+        let address = format!("unix://{}", path.display());
+        let mut client = GrpcClient::from_address(&address, None, Transport::Grpc).unwrap();
+        let files = client.reflect_file_descriptors().await;
+        let _ = std::fs::remove_file(&path);
+
+        // then
+        let desc = ProtoDescriptor::from_file_descriptor_protos(files.unwrap()).unwrap();
+        assert!(desc
+            .get_method_by_name("proto.TestService", "Simple")
+            .is_some());
+    }
+}
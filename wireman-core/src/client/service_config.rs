@@ -0,0 +1,352 @@
+//! The `gRPC` service config, which defines the timeouts and the retry
+//! policies of the methods. See
+//! <https://github.com/grpc/grpc/blob/master/doc/service_config.md>.
+use crate::descriptor::RequestMessage;
+use crate::error::{code_from_str, Error};
+use crate::Result;
+use prost_reflect::MethodDescriptor;
+use serde::{Deserialize, Deserializer};
+use std::path::Path;
+use std::time::Duration;
+use tokio::time::Instant;
+use tonic::Code;
+
+/// The maximum number of attempts of a call, larger values of the
+/// retry policy are capped.
+const MAX_ATTEMPTS: u32 = 5;
+
+/// The backoff between connection attempts of wait-for-ready calls.
+const WAIT_FOR_READY_BACKOFF: Duration = Duration::from_millis(200);
+
+/// A `gRPC` service config.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ServiceConfig {
+    /// The configs of the methods.
+    #[serde(default)]
+    pub method_config: Vec<MethodConfig>,
+}
+
+impl ServiceConfig {
+    /// Parses a service config from a json string.
+    ///
+    /// # Errors
+    /// - The json is not a valid service config
+    pub fn from_json(json: &str) -> Result<Self> {
+        serde_json::from_str(json).map_err(|err| Error::ServiceConfigError(err.to_string()))
+    }
+
+    /// Reads a service config from a json file.
+    ///
+    /// # Errors
+    /// - Failed to read the file
+    /// - The file is not a valid service config
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let json = std::fs::read_to_string(path).map_err(Error::ReadServiceConfigError)?;
+        Self::from_json(&json)
+    }
+
+    /// Returns the config of a method. A config which names the method
+    /// takes precedence over a config which names its service, which
+    /// takes precedence over the default config without a service.
+    #[must_use]
+    pub fn method_config(&self, method: &MethodDescriptor) -> Option<&MethodConfig> {
+        let service = method.parent_service().full_name().to_string();
+        let method = method.name().to_string();
+        let find = |service: Option<&str>, method: Option<&str>| {
+            self.method_config.iter().find(|config| {
+                config.name.iter().any(|name| {
+                    name.service.as_deref().filter(|s| !s.is_empty()) == service
+                        && name.method.as_deref().filter(|m| !m.is_empty()) == method
+                })
+            })
+        };
+        find(Some(&service), Some(&method))
+            .or_else(|| find(Some(&service), None))
+            .or_else(|| find(None, None))
+    }
+}
+
+/// The config of a set of methods.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MethodConfig {
+    /// The methods to which the config applies.
+    #[serde(default)]
+    pub name: Vec<MethodName>,
+    /// Whether calls wait for the connection to become ready instead
+    /// of failing if the server is unavailable.
+    #[serde(default)]
+    pub wait_for_ready: bool,
+    /// The default timeout of the calls. An explicit timeout of a call
+    /// is used if it is shorter.
+    #[serde(default, deserialize_with = "deserialize_duration")]
+    pub timeout: Option<Duration>,
+    /// The retry policy of the calls.
+    pub retry_policy: Option<RetryPolicy>,
+}
+
+/// The name of a method, or of all methods of a service if the method
+/// is omitted, or of all methods if both are omitted.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+pub struct MethodName {
+    /// The full name of the service, e.g. `grpc.health.v1.Health`.
+    pub service: Option<String>,
+    /// The name of the method, e.g. `Check`.
+    pub method: Option<String>,
+}
+
+/// The policy of retrying failed calls with exponential backoff.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RetryPolicy {
+    /// The maximum number of attempts, including the original call.
+    pub max_attempts: u32,
+    /// The backoff before the first retry.
+    #[serde(deserialize_with = "deserialize_required_duration")]
+    pub initial_backoff: Duration,
+    /// The upper bound of the backoff.
+    #[serde(deserialize_with = "deserialize_required_duration")]
+    pub max_backoff: Duration,
+    /// The factor by which the backoff grows after each retry.
+    pub backoff_multiplier: f64,
+    /// The status codes on which a call is retried.
+    #[serde(deserialize_with = "deserialize_codes")]
+    pub retryable_status_codes: Vec<Code>,
+}
+
+/// Retries the attempts of a call as defined by its method config.
+#[derive(Debug)]
+pub(super) struct Retry {
+    config: Option<MethodConfig>,
+    /// The deadline of the call, which spans all attempts.
+    deadline: Option<Instant>,
+    /// The number of started attempts.
+    attempts: u32,
+    /// The backoff before the next retry.
+    backoff: Duration,
+}
+
+impl Retry {
+    /// Returns the retry state of a call with the given timeout.
+    pub(super) fn new(config: Option<MethodConfig>, timeout: Option<Duration>) -> Self {
+        let timeout = match (timeout, config.as_ref().and_then(|c| c.timeout)) {
+            (Some(a), Some(b)) => Some(a.min(b)),
+            (a, b) => a.or(b),
+        };
+        let backoff = config
+            .as_ref()
+            .and_then(|c| c.retry_policy.as_ref())
+            .map_or(WAIT_FOR_READY_BACKOFF, |policy| policy.initial_backoff);
+        Self {
+            config,
+            deadline: timeout.map(|timeout| Instant::now() + timeout),
+            attempts: 0,
+            backoff,
+        }
+    }
+
+    /// The number of started attempts.
+    pub(super) fn attempts(&self) -> u32 {
+        self.attempts
+    }
+
+    /// Starts the next attempt. Returns the request with the remaining
+    /// time of the call as timeout.
+    pub(super) fn request(&mut self, req: &RequestMessage) -> RequestMessage {
+        self.attempts += 1;
+        let mut req = req.clone();
+        if let Some(deadline) = self.deadline {
+            req.set_timeout(Some(deadline.saturating_duration_since(Instant::now())));
+        }
+        req
+    }
+
+    /// Whether the failed attempt is retried. Waits for a random time
+    /// between zero and the backoff before returning, as defined by
+    /// the `gRPC` retry design.
+    pub(super) async fn retry(&mut self, err: &Error) -> bool {
+        let Some(config) = &self.config else {
+            return false;
+        };
+        let max_attempts = config
+            .retry_policy
+            .as_ref()
+            .map_or(MAX_ATTEMPTS, |policy| policy.max_attempts.min(MAX_ATTEMPTS));
+        if self.attempts >= max_attempts {
+            return false;
+        }
+        let wait_for_ready = config.wait_for_ready && err.is_unavailable();
+        let retryable = config.retry_policy.as_ref().is_some_and(|policy| {
+            matches!(err, Error::GrpcError(status) if policy.retryable_status_codes.contains(&status.code))
+        });
+        if !wait_for_ready && !retryable {
+            return false;
+        }
+        let wake = Instant::now() + jitter(self.backoff);
+        if self.deadline.is_some_and(|deadline| wake >= deadline) {
+            return false;
+        }
+        tokio::time::sleep_until(wake).await;
+        if let Some(policy) = &config.retry_policy {
+            let backoff = self.backoff.mul_f64(policy.backoff_multiplier.max(1.0));
+            self.backoff = backoff.min(policy.max_backoff);
+        }
+        true
+    }
+}
+
+/// Returns a random duration between zero and the backoff.
+fn jitter(backoff: Duration) -> Duration {
+    backoff.mul_f64(rand::random::<f64>())
+}
+
+/// Parses a duration in the json format of `google.protobuf.Duration`,
+/// e.g. `1.5s`.
+fn parse_duration(value: &str) -> std::result::Result<Duration, String> {
+    value
+        .strip_suffix('s')
+        .and_then(|secs| secs.parse::<f64>().ok())
+        .and_then(|secs| Duration::try_from_secs_f64(secs).ok())
+        .ok_or_else(|| format!("invalid duration: {value}"))
+}
+
+fn deserialize_duration<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> std::result::Result<Option<Duration>, D::Error> {
+    Option::<String>::deserialize(deserializer)?
+        .map(|value| parse_duration(&value).map_err(serde::de::Error::custom))
+        .transpose()
+}
+
+fn deserialize_required_duration<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> std::result::Result<Duration, D::Error> {
+    parse_duration(&String::deserialize(deserializer)?).map_err(serde::de::Error::custom)
+}
+
+/// A status code, either by its name or its number.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum StatusCode {
+    Number(i32),
+    Name(String),
+}
+
+impl StatusCode {
+    fn into_code<E: serde::de::Error>(self) -> std::result::Result<Code, E> {
+        match self {
+            StatusCode::Number(number) => Ok(Code::from_i32(number)),
+            StatusCode::Name(name) => code_from_str(&name.to_ascii_lowercase())
+                .ok_or_else(|| E::custom(format!("invalid status code: {name}"))),
+        }
+    }
+}
+
+fn deserialize_codes<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> std::result::Result<Vec<Code>, D::Error> {
+    Vec::<StatusCode>::deserialize(deserializer)?
+        .into_iter()
+        .map(StatusCode::into_code)
+        .collect()
+}
+
+/// Deserializes a status code, either by its name, e.g. `UNAVAILABLE`,
+/// or by its number.
+pub(crate) fn deserialize_code<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> std::result::Result<Code, D::Error> {
+    StatusCode::deserialize(deserializer)?.into_code()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::ProtoDescriptor;
+
+    const SERVICE_CONFIG: &str = r#"{
+        "methodConfig": [
+            {
+                "name": [{"service": "proto.TestService", "method": "Simple"}],
+                "timeout": "1.5s",
+                "retryPolicy": {
+                    "maxAttempts": 4,
+                    "initialBackoff": "0.1s",
+                    "maxBackoff": "1s",
+                    "backoffMultiplier": 2,
+                    "retryableStatusCodes": ["UNAVAILABLE", 10]
+                }
+            },
+            {
+                "name": [{}],
+                "waitForReady": true
+            }
+        ]
+    }"#;
+
+    fn load_method(name: &str) -> MethodDescriptor {
+        let desc = ProtoDescriptor::new(vec!["."], vec!["test_files/test.proto"]).unwrap();
+        desc.get_method_by_name("proto.TestService", name).unwrap()
+    }
+
+    #[test]
+    fn test_from_json() {
+        // given
+        let method = load_method("Simple");
+
+        // when
+        let config = ServiceConfig::from_json(SERVICE_CONFIG).unwrap();
+
+        // then
+        let method_config = config.method_config(&method).unwrap();
+        assert_eq!(method_config.timeout, Some(Duration::from_millis(1500)));
+        assert!(!method_config.wait_for_ready);
+        let policy = method_config.retry_policy.as_ref().unwrap();
+        assert_eq!(policy.max_attempts, 4);
+        assert_eq!(policy.initial_backoff, Duration::from_millis(100));
+        assert_eq!(
+            policy.retryable_status_codes,
+            vec![Code::Unavailable, Code::Aborted]
+        );
+    }
+
+    #[test]
+    fn test_method_config_default() {
+        // given
+        let config = ServiceConfig::from_json(SERVICE_CONFIG).unwrap();
+        let method = load_method("Multiple");
+
+        // when
+        let method_config = config.method_config(&method).unwrap();
+
+        // then
+        assert!(method_config.wait_for_ready);
+        assert!(method_config.retry_policy.is_none());
+    }
+
+    #[test]
+    fn test_from_json_invalid_duration() {
+        // given
+        let json = r#"{"methodConfig": [{"name": [{}], "timeout": "1.5"}]}"#;
+
+        // when
+        let result = ServiceConfig::from_json(json);
+
+        // then
+        assert!(matches!(result, Err(Error::ServiceConfigError(_))));
+    }
+
+    #[test]
+    fn test_jitter() {
+        // given
+        let backoff = Duration::from_millis(100);
+
+        // when
+        let waits: Vec<_> = (0..100).map(|_| jitter(backoff)).collect();
+
+        // then
+        assert!(waits.iter().all(|wait| *wait <= backoff));
+        assert!(waits.iter().any(|wait| *wait != waits[0]));
+    }
+}
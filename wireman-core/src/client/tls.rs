@@ -1,80 +1,193 @@
 #![allow(clippy::module_name_repetitions)]
 use crate::error::{Error, Result};
-use hyper::client::HttpConnector;
-use hyper_rustls::HttpsConnector;
+use http::uri::Authority;
+use hyper_rustls::builderstates::WantsProtocols1;
 use hyper_rustls::HttpsConnectorBuilder;
-use rustls::pki_types::CertificateDer;
-use rustls::RootCertStore;
+use rustls::client::danger::{HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier};
+use rustls::crypto::{verify_tls12_signature, verify_tls13_signature, CryptoProvider};
+use rustls::pki_types::{CertificateDer, PrivateKeyDer, ServerName, UnixTime};
+use rustls::{DigitallySignedStruct, RootCertStore, SignatureScheme};
 use serde::{Deserialize, Serialize};
+use std::sync::Arc;
 
 /// The TLS config of the grpc client.
-#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq, Eq, PartialOrd)]
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq, Eq, PartialOrd, Hash)]
 pub struct TlsConfig {
     /// Custom certificates
     custom_cert: Option<String>,
+    /// The client certificate used for mutual TLS
+    client_cert: Option<String>,
+    /// The private key of the client certificate
+    client_key: Option<String>,
+    /// Whether the server certificate is accepted without verification
+    #[serde(default)]
+    insecure: bool,
+    /// Overrides the TLS server name and the HTTP/2 `:authority`
+    authority: Option<String>,
 }
 
 impl TlsConfig {
     /// Create a new `TlsConfig` with optional custom certificates.
     #[must_use]
     pub fn new(custom_cert: Option<String>) -> Self {
-        Self { custom_cert }
+        Self {
+            custom_cert,
+            ..Default::default()
+        }
+    }
+
+    /// Sets the PEM encoded client certificate and private key which
+    /// are presented to the server for mutual TLS.
+    #[must_use]
+    pub fn with_client_auth(
+        mut self,
+        client_cert: Option<String>,
+        client_key: Option<String>,
+    ) -> Self {
+        self.client_cert = client_cert;
+        self.client_key = client_key;
+        self
     }
 
-    /// Get the HTTPS connector based on the TLS configuration.
+    /// Skips the verification of the server certificate. Should only be
+    /// used for local development, e.g. with self-signed certificates.
     #[must_use]
-    pub fn get_connector_from_tls(&self) -> HttpsConnector<HttpConnector> {
-        let tls = self.get_client_config();
+    pub fn with_insecure(mut self, insecure: bool) -> Self {
+        self.insecure = insecure;
+        self
+    }
 
-        let mut http = HttpConnector::new();
-        http.enforce_http(false);
+    /// Overrides the TLS server name and the HTTP/2 `:authority`. Useful
+    /// when connecting by IP or through a port-forward.
+    #[must_use]
+    pub fn with_authority(mut self, authority: Option<String>) -> Self {
+        self.authority = authority;
+        self
+    }
 
-        HttpsConnectorBuilder::new()
+    /// Whether the server certificate is accepted without verification.
+    #[must_use]
+    pub fn insecure(&self) -> bool {
+        self.insecure
+    }
+
+    /// Returns the `:authority` override, if any.
+    #[must_use]
+    pub fn authority(&self) -> Option<&str> {
+        self.authority.as_deref()
+    }
+
+    /// Returns the connector builder with the tls settings and the
+    /// server name applied.
+    pub(super) fn connector_builder(&self) -> Result<HttpsConnectorBuilder<WantsProtocols1>> {
+        let tls = self.get_client_config()?;
+
+        let mut builder = HttpsConnectorBuilder::new()
             .with_tls_config(tls)
-            .https_or_http()
-            .enable_http2()
-            .wrap_connector(http)
+            .https_or_http();
+        if let Some(authority) = &self.authority {
+            let authority = authority
+                .parse::<Authority>()
+                .map_err(|_| Error::InvalidAuthority(authority.clone()))?;
+            builder = builder.with_server_name(authority.host().to_string());
+        }
+        Ok(builder)
     }
 
     /// Get the client's TLS configuration as a `rustls::ClientConfig`.
-    pub fn get_client_config(&self) -> rustls::ClientConfig {
-        if let Some(cert) = &self.custom_cert {
-            return get_client_config_with_custom_certs(cert).unwrap();
+    ///
+    /// # Errors
+    /// - Failed to load the custom certificate
+    /// - Failed to load the client certificate or key
+    /// - Only one of client certificate and key is given
+    pub fn get_client_config(&self) -> Result<rustls::ClientConfig> {
+        let builder = if self.insecure {
+            rustls::ClientConfig::builder()
+                .dangerous()
+                .with_custom_certificate_verifier(Arc::new(SkipServerVerification::new()))
+        } else {
+            let roots = match &self.custom_cert {
+                Some(cert) => root_store_from_cert_file(cert)?,
+                None => RootCertStore {
+                    roots: webpki_roots::TLS_SERVER_ROOTS.into(),
+                },
+            };
+            rustls::ClientConfig::builder().with_root_certificates(roots)
+        };
+
+        match (&self.client_cert, &self.client_key) {
+            (Some(cert), Some(key)) => {
+                let cert_chain = certs_from_file(cert).map_err(Error::LoadTLSClientCertError)?;
+                let key_der = private_key_from_file(key)?;
+                builder
+                    .with_client_auth_cert(cert_chain, key_der)
+                    .map_err(Error::TLSClientAuthError)
+            }
+            (None, None) => Ok(builder.with_no_client_auth()),
+            _ => Err(Error::IncompleteTLSClientAuth),
         }
-        get_client_config_native_certs()
     }
 }
 
-/// Defaults TLS client config with native roots.
-fn get_client_config_native_certs() -> rustls::ClientConfig {
-    let root_store = RootCertStore {
-        roots: webpki_roots::TLS_SERVER_ROOTS.into(),
-    };
-    rustls::ClientConfig::builder()
-        .with_root_certificates(root_store)
-        .with_no_client_auth()
+/// A server certificate verifier which accepts any certificate. The
+/// handshake signatures are still verified.
+#[derive(Debug)]
+struct SkipServerVerification(Arc<CryptoProvider>);
+
+impl SkipServerVerification {
+    fn new() -> Self {
+        Self(Arc::new(rustls::crypto::ring::default_provider()))
+    }
 }
 
-/// Get TLS client configuration with a custom certificate.
-fn get_client_config_with_custom_certs(ca_cert: &str) -> Result<rustls::ClientConfig> {
-    let roots = root_store_from_cert_file(ca_cert)?;
-    let config = rustls::ClientConfig::builder()
-        .with_root_certificates(roots)
-        .with_no_client_auth();
-    Ok(config)
+impl ServerCertVerifier for SkipServerVerification {
+    fn verify_server_cert(
+        &self,
+        _end_entity: &CertificateDer<'_>,
+        _intermediates: &[CertificateDer<'_>],
+        _server_name: &ServerName<'_>,
+        _ocsp_response: &[u8],
+        _now: UnixTime,
+    ) -> std::result::Result<ServerCertVerified, rustls::Error> {
+        Ok(ServerCertVerified::assertion())
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> std::result::Result<HandshakeSignatureValid, rustls::Error> {
+        verify_tls12_signature(
+            message,
+            cert,
+            dss,
+            &self.0.signature_verification_algorithms,
+        )
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> std::result::Result<HandshakeSignatureValid, rustls::Error> {
+        verify_tls13_signature(
+            message,
+            cert,
+            dss,
+            &self.0.signature_verification_algorithms,
+        )
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.0.signature_verification_algorithms.supported_schemes()
+    }
 }
 
 // Load public certificate from file.
 fn root_store_from_cert_file(filename: &str) -> Result<RootCertStore> {
-    // Open certificate file.
-    let certfile = std::fs::File::open(filename).map_err(Error::LoadTLSCertificateError)?;
-    let mut reader = std::io::BufReader::new(certfile);
-
-    // Read the certificates
-    let certs: Vec<CertificateDer> = rustls_pemfile::certs(&mut reader)
-        .into_iter()
-        .filter_map(|result| result.ok())
-        .collect();
+    let certs = certs_from_file(filename).map_err(Error::LoadTLSCertificateError)?;
 
     // Parse the certificates
     let mut roots = rustls::RootCertStore::empty();
@@ -82,3 +195,97 @@ fn root_store_from_cert_file(filename: &str) -> Result<RootCertStore> {
 
     Ok(roots)
 }
+
+// Read all PEM encoded certificates from a file.
+fn certs_from_file(filename: &str) -> std::io::Result<Vec<CertificateDer<'static>>> {
+    let certfile = std::fs::File::open(filename)?;
+    let mut reader = std::io::BufReader::new(certfile);
+
+    Ok(rustls_pemfile::certs(&mut reader)
+        .filter_map(|result| result.ok())
+        .collect())
+}
+
+// Read the first PEM encoded private key from a file.
+fn private_key_from_file(filename: &str) -> Result<PrivateKeyDer<'static>> {
+    let keyfile = std::fs::File::open(filename).map_err(Error::LoadTLSClientKeyError)?;
+    let mut reader = std::io::BufReader::new(keyfile);
+
+    rustls_pemfile::private_key(&mut reader)
+        .map_err(Error::LoadTLSClientKeyError)?
+        .ok_or(Error::NoTLSClientKey)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const CLIENT_CERT: &str = "test_files/tls/client.pem";
+    const CLIENT_KEY: &str = "test_files/tls/client.key";
+
+    #[test]
+    fn test_client_auth() {
+        // given
+        let tls = TlsConfig::default()
+            .with_client_auth(Some(CLIENT_CERT.to_string()), Some(CLIENT_KEY.to_string()));
+
+        // when
+        let config = tls.get_client_config().unwrap();
+
+        // then
+        assert!(config.client_auth_cert_resolver.has_certs());
+    }
+
+    #[test]
+    fn test_client_auth_missing_key_file() {
+        // given
+        let tls = TlsConfig::default().with_client_auth(
+            Some(CLIENT_CERT.to_string()),
+            Some("test_files/tls/missing.key".to_string()),
+        );
+
+        // when
+        let result = tls.get_client_config();
+
+        // then
+        assert!(matches!(result, Err(Error::LoadTLSClientKeyError(_))));
+    }
+
+    #[test]
+    fn test_client_auth_invalid_key_file() {
+        // given
+        let tls = TlsConfig::default()
+            .with_client_auth(Some(CLIENT_CERT.to_string()), Some(CLIENT_CERT.to_string()));
+
+        // when
+        let result = tls.get_client_config();
+
+        // then
+        assert!(matches!(result, Err(Error::NoTLSClientKey)));
+    }
+
+    #[test]
+    fn test_insecure_ignores_custom_cert() {
+        // given
+        let tls =
+            TlsConfig::new(Some("test_files/tls/missing.pem".to_string())).with_insecure(true);
+
+        // when
+        let result = tls.get_client_config();
+
+        // then
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn test_client_auth_incomplete() {
+        // given
+        let tls = TlsConfig::default().with_client_auth(Some(CLIENT_CERT.to_string()), None);
+
+        // when
+        let result = tls.get_client_config();
+
+        // then
+        assert!(matches!(result, Err(Error::IncompleteTLSClientAuth)));
+    }
+}
@@ -0,0 +1,183 @@
+//! The transports over which `gRPC` calls are sent.
+use super::connection::ConnectionConfig;
+use super::metrics::ConnectTime;
+use super::tls::TlsConfig;
+use crate::error::Error;
+use crate::Result;
+use bytes::Bytes;
+use http::header::HOST;
+use http::uri::Scheme;
+use http::{HeaderValue, Request, Response, Uri};
+use http_body::Body;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use tonic::body::BoxBody;
+use tonic::transport::Channel;
+use tonic::Status;
+use tonic_web::{GrpcWebCall, GrpcWebClientLayer};
+use tower::util::BoxCloneService;
+use tower::{ServiceBuilder, ServiceExt};
+
+/// A type erased error of a http service.
+type BoxError = Box<dyn std::error::Error + Send + Sync>;
+
+/// A type erased http service over which calls are sent.
+pub(super) type HttpService = BoxCloneService<Request<BoxBody>, Response<BoxBody>, TransportError>;
+
+/// The error of the http service over which calls are sent.
+#[derive(Debug)]
+pub struct TransportError(BoxError);
+
+impl TransportError {
+    fn new(err: impl Into<BoxError>) -> Self {
+        Self(err.into())
+    }
+}
+
+impl fmt::Display for TransportError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl std::error::Error for TransportError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        self.0.source()
+    }
+}
+
+/// The protocol which is spoken with the server.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Transport {
+    /// Native `gRPC` over HTTP/2.
+    #[default]
+    Grpc,
+    /// `gRPC-Web` (`application/grpc-web+proto`) over HTTP/1.1. The
+    /// trailers are sent at the end of the response body. Spoken by
+    /// Envoy's `gRPC-Web` filter and browser-facing gateways.
+    GrpcWeb,
+    /// The Connect protocol over HTTP/1.1 with binary messages
+    /// (`application/proto`). Unary calls are plain `POST` requests.
+    Connect,
+    /// The Connect protocol over HTTP/1.1 with json messages
+    /// (`application/json`).
+    ConnectJson,
+}
+
+impl Transport {
+    /// Whether the transport supports client and bidirectional
+    /// streaming calls.
+    #[must_use]
+    pub fn supports_client_streaming(self) -> bool {
+        self == Self::Grpc
+    }
+}
+
+impl fmt::Display for Transport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Grpc => write!(f, "gRPC"),
+            Self::GrpcWeb => write!(f, "gRPC-Web"),
+            Self::Connect => write!(f, "Connect"),
+            Self::ConnectJson => write!(f, "Connect (JSON)"),
+        }
+    }
+}
+
+/// Converts a response body into a [`BoxBody`].
+fn boxed<B>(body: B) -> BoxBody
+where
+    B: Body<Data = Bytes> + Send + 'static,
+    B::Error: Into<BoxError>,
+{
+    body.map_err(|err| Status::from_error(err.into()))
+        .boxed_unsync()
+}
+
+/// Wraps a native `gRPC` channel.
+pub(super) fn grpc(channel: Channel) -> HttpService {
+    let service = channel
+        .map_response(|response| response.map(boxed))
+        .map_err(TransportError::new);
+    BoxCloneService::new(service)
+}
+
+/// Returns a `gRPC-Web` service. The requests are translated by the
+/// `tonic-web` client layer and sent over HTTP/1.1, `https` uris are
+/// connected via TLS. The authority override of the tls settings is
+/// sent as `Host` header. The duration of new connections is recorded
+/// in `connect_time`.
+///
+/// # Errors
+/// - Failed to load the tls certificates or the client key
+/// - The authority override is invalid
+pub(super) fn grpc_web(
+    uri: &Uri,
+    connection: &ConnectionConfig,
+    connect_time: &ConnectTime,
+) -> Result<HttpService> {
+    let host = host_header(connection.tls())?;
+    let service = ServiceBuilder::new()
+        .map_request(move |request| set_host(request, host.as_ref()))
+        .layer(GrpcWebClientLayer::new())
+        .service(http1_client::<GrpcWebCall<BoxBody>>(
+            uri,
+            connection,
+            connect_time,
+        )?)
+        .map_response(|response| response.map(boxed))
+        .map_err(TransportError::new);
+    Ok(BoxCloneService::new(service))
+}
+
+/// A type erased HTTP/1.1 client.
+pub(super) type Http1Client<B> = BoxCloneService<Request<B>, Response<hyper::Body>, hyper::Error>;
+
+/// Returns a HTTP/1.1 client. `https` uris are connected via TLS. The
+/// duration of new connections is recorded in `connect_time`.
+///
+/// # Errors
+/// - Failed to load the tls certificates or the client key
+/// - The authority override is invalid
+pub(super) fn http1_client<B>(
+    uri: &Uri,
+    connection: &ConnectionConfig,
+    connect_time: &ConnectTime,
+) -> Result<Http1Client<B>>
+where
+    B: Body + Send + 'static,
+    B::Data: Send,
+    B::Error: Into<BoxError>,
+{
+    if uri.scheme() == Some(&Scheme::HTTPS) {
+        let connector = connection.get_http1_connector_from_tls()?;
+        return Ok(BoxCloneService::new(
+            hyper::Client::builder().build(connect_time.wrap(connector)),
+        ));
+    }
+    Ok(BoxCloneService::new(hyper::Client::builder().build(
+        connect_time.wrap(connection.get_proxy_connector()?),
+    )))
+}
+
+/// Returns the authority override of the tls settings as `Host` header.
+///
+/// # Errors
+/// - The authority override is invalid
+pub(super) fn host_header(tls: &TlsConfig) -> Result<Option<HeaderValue>> {
+    tls.authority()
+        .map(|authority| {
+            HeaderValue::from_str(authority)
+                .map_err(|_| Error::InvalidAuthority(authority.to_string()))
+        })
+        .transpose()
+}
+
+/// Sets the `Host` header of a request, if given.
+pub(super) fn set_host<B>(mut request: Request<B>, host: Option<&HeaderValue>) -> Request<B> {
+    if let Some(host) = host {
+        request.headers_mut().insert(HOST, host.clone());
+    }
+    request
+}
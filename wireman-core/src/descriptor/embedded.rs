@@ -0,0 +1,66 @@
+//! Proto files that are compiled into the binary, such as the
+//! `google.rpc` error model and the `grpc.health.v1` health service.
+use crate::error::Error;
+use crate::Result;
+use prost_reflect::DescriptorPool;
+use protox::file::{ChainFileResolver, File, FileResolver, GoogleFileResolver};
+use protox::Compiler;
+
+/// The embedded proto files by their import name.
+const FILES: &[(&str, &str)] = &[
+    (
+        "google/rpc/status.proto",
+        include_str!("../../protos/google/rpc/status.proto"),
+    ),
+    (
+        "google/rpc/error_details.proto",
+        include_str!("../../protos/google/rpc/error_details.proto"),
+    ),
+    (
+        "grpc/health/v1/health.proto",
+        include_str!("../../protos/grpc/health/v1/health.proto"),
+    ),
+];
+
+/// Resolves the embedded proto files.
+struct EmbeddedFileResolver;
+
+impl FileResolver for EmbeddedFileResolver {
+    fn open_file(&self, name: &str) -> std::result::Result<File, protox::Error> {
+        match FILES.iter().find(|(file, _)| *file == name) {
+            Some((name, source)) => File::from_source(name, source),
+            None => Err(protox::Error::file_not_found(name)),
+        }
+    }
+}
+
+/// Compiles embedded proto files into a descriptor pool. Imports of
+/// the well known `google.protobuf` types are resolved as well.
+///
+/// # Errors
+/// - Failed to compile proto `ProtoxCompileError`
+pub(crate) fn compile(files: &[&str]) -> Result<DescriptorPool> {
+    let mut resolver = ChainFileResolver::new();
+    resolver.add(EmbeddedFileResolver);
+    resolver.add(GoogleFileResolver::new());
+    let mut compiler = Compiler::with_file_resolver(resolver);
+    compiler
+        .open_files(files)
+        .map_err(Error::ProtoxCompileError)?;
+    Ok(compiler.descriptor_pool())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_compile() {
+        // when
+        let pool = compile(&["google/rpc/status.proto"]).unwrap();
+
+        // then
+        assert!(pool.get_message_by_name("google.rpc.Status").is_some());
+        assert!(pool.get_message_by_name("google.protobuf.Any").is_some());
+    }
+}
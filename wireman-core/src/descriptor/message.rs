@@ -5,6 +5,7 @@ use crate::{
     error::{Error, FROM_UTF8},
     Result,
 };
+use prost::Message;
 use prost_reflect::{
     DeserializeOptions, DynamicMessage as DynMessage, MessageDescriptor, ReflectMessage,
     SerializeOptions,
@@ -85,6 +86,25 @@ impl DynamicMessage {
         String::from_utf8(s.into_inner()).map_err(|_| Error::Internal(FROM_UTF8.to_string()))
     }
 
+    /// Decode a `DynamicMessage` from its protobuf encoding. Fields
+    /// which are not part of the descriptor are kept as unknown fields.
+    ///
+    /// # Errors
+    ///
+    /// - Failed to decode message.
+    pub fn from_bytes(&mut self, bytes: &[u8]) -> Result<()> {
+        let msg = DynMessage::decode(self.descriptor(), bytes)
+            .map_err(|err| Error::InvalidRawPayload(err.to_string()))?;
+        self.inner = msg;
+        Ok(())
+    }
+
+    /// Encode a `DynamicMessage` to its protobuf encoding.
+    #[must_use]
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.inner.encode_to_vec()
+    }
+
     /// Apply default values to a `DynamicMessage`.
     pub fn apply_template(&mut self) {
         apply_template_for_message(self, 0);
@@ -1,10 +1,26 @@
 use std::ops::{Deref, DerefMut};
 
+use base64::alphabet::STANDARD;
+use base64::engine::{DecodePaddingMode, GeneralPurpose, GeneralPurposeConfig};
+use base64::Engine;
 use serde::{ser::SerializeMap, Serialize, Serializer};
-use tonic::metadata::{Ascii, KeyRef, MetadataKey, MetadataMap, MetadataValue};
+use tonic::metadata::{Ascii, Binary, KeyAndValueRef, MetadataKey, MetadataMap, MetadataValue};
 
 use crate::error::Error;
 
+/// The header which announces the compression of the messages.
+const GRPC_ENCODING: &str = "grpc-encoding";
+
+/// The suffix of the keys of binary metadata.
+const BINARY_SUFFIX: &str = "-bin";
+
+/// The standard base64 encoding with optional padding. Used for binary
+/// metadata values, raw payloads and the error details.
+pub(crate) const BASE64: GeneralPurpose = GeneralPurpose::new(
+    &STANDARD,
+    GeneralPurposeConfig::new().with_decode_padding_mode(DecodePaddingMode::Indifferent),
+);
+
 /// Represents `gRPC` metadata, which contains key-value pairs. Metadata is commonly used to
 /// provide additional information with `gRPC` requests and responses.
 #[derive(Debug, Clone)]
@@ -40,27 +56,66 @@ impl Metadata {
         Ok(())
     }
 
+    /// Insert a key-value pair into the metadata. The values of keys
+    /// with a `-bin` suffix are base64 decoded and sent as binary
+    /// metadata, all other values must be valid header values.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error naming the key if the key or the value is invalid.
+    pub fn insert_str(&mut self, key: &str, val: &str) -> crate::error::Result<()> {
+        let invalid = |reason: &str| Error::InvalidMetadata(key.to_string(), reason.to_string());
+        if key.ends_with(BINARY_SUFFIX) {
+            let key: MetadataKey<Binary> = key.parse().map_err(|_| invalid("invalid key"))?;
+            let bytes = BASE64
+                .decode(val.trim())
+                .map_err(|_| invalid("value is not valid base64"))?;
+            self.insert_bin(key, MetadataValue::from_bytes(&bytes));
+        } else {
+            let key: MetadataKey<Ascii> = key.parse().map_err(|_| invalid("invalid key"))?;
+            let val: MetadataValue<Ascii> = val.parse().map_err(|_| invalid("invalid value"))?;
+            self.insert(key, val);
+        }
+        Ok(())
+    }
+
+    /// Returns the key value pairs as strings in the order in which they
+    /// were received. Binary values are returned base64 encoded.
+    #[must_use]
+    pub fn to_pairs(&self) -> Vec<(String, String)> {
+        self.inner
+            .iter()
+            .map(|entry| match entry {
+                KeyAndValueRef::Ascii(key, val) => (
+                    key.to_string(),
+                    String::from_utf8_lossy(val.as_encoded_bytes()).to_string(),
+                ),
+                KeyAndValueRef::Binary(key, val) => (
+                    key.to_string(),
+                    String::from_utf8_lossy(val.as_encoded_bytes()).to_string(),
+                ),
+            })
+            .collect()
+    }
+
+    /// Returns the compression of the messages as announced by the
+    /// `grpc-encoding` header. Returns `None` if the messages are not
+    /// compressed.
+    #[must_use]
+    pub fn encoding(&self) -> Option<&str> {
+        self.inner
+            .get(GRPC_ENCODING)
+            .and_then(|val| val.to_str().ok())
+            .filter(|encoding| *encoding != "identity")
+    }
+
     /// Serialize the metadata to a format compatible with the given serializer.
     ///
     /// # Errors
     ///
     /// Returns an error if serializing the metadata map fails
     pub fn serialize<S: Serializer>(&self, ser: S) -> Result<S::Ok, S::Error> {
-        let len = self.inner.len();
-        let mut map = ser.serialize_map(Some(len))?;
-        for key in self.inner.keys() {
-            if let KeyRef::Ascii(ascii_key) = key {
-                let Some(value) = self.inner.get(ascii_key) else {
-                    continue;
-                };
-                let key_str = ascii_key.to_string();
-                let Ok(value_str) = value.to_str() else {
-                    continue;
-                };
-                map.serialize_entry(&key_str, value_str)?;
-            }
-        }
-        map.end()
+        Serialize::serialize(self, ser)
     }
 }
 
@@ -69,20 +124,22 @@ impl Serialize for Metadata {
     where
         S: Serializer,
     {
-        let len = self.inner.len();
-        let mut map = serializer.serialize_map(Some(len))?;
-        for key in self.inner.keys() {
-            if let KeyRef::Ascii(ascii_key) = key {
-                let value = self.inner.get(ascii_key).unwrap();
-                let key_str = ascii_key.to_string();
-                let value_str = value.to_str().unwrap();
-                map.serialize_entry(&key_str, value_str)?;
-            }
+        // Binary values are serialized base64 encoded
+        let pairs = self.to_pairs();
+        let mut map = serializer.serialize_map(Some(pairs.len()))?;
+        for (key, value) in &pairs {
+            map.serialize_entry(key, value)?;
         }
         map.end()
     }
 }
 
+impl From<MetadataMap> for Metadata {
+    fn from(inner: MetadataMap) -> Self {
+        Self { inner }
+    }
+}
+
 impl Deref for Metadata {
     type Target = MetadataMap;
 
@@ -119,4 +176,59 @@ mod test {
         let expected_json = "{\"auth\":\"bearer\",\"alias\":\"xx\"}";
         assert_eq!(json, expected_json);
     }
+
+    #[test]
+    fn test_insert_str_binary() {
+        // given
+        let mut metadata = Metadata::new();
+
+        // when
+        metadata.insert_str("trace-bin", "aGVsbG8=").unwrap();
+        metadata.insert_str("x-request-id", "42").unwrap();
+
+        // then
+        let value = metadata.get_bin("trace-bin").unwrap();
+        assert_eq!(value.to_bytes().unwrap().as_ref(), b"hello");
+        let json = serde_json::to_string(&metadata).unwrap();
+        assert_eq!(json, "{\"trace-bin\":\"aGVsbG8\",\"x-request-id\":\"42\"}");
+    }
+
+    #[test]
+    fn test_insert_str_invalid() {
+        // given
+        let mut metadata = Metadata::new();
+
+        // when
+        let binary = metadata.insert_str("trace-bin", "not base64!");
+        let ascii = metadata.insert_str("x-name", "line\nbreak");
+
+        // then
+        assert_eq!(
+            binary.unwrap_err().to_string(),
+            "invalid header trace-bin: value is not valid base64"
+        );
+        assert_eq!(
+            ascii.unwrap_err().to_string(),
+            "invalid header x-name: invalid value"
+        );
+        assert!(metadata.is_empty());
+    }
+
+    #[test]
+    fn test_to_pairs() {
+        // given
+        let mut metadata = Metadata::new();
+        metadata.insert_ascii("x-request-id", "42").unwrap();
+        metadata.insert_bin("trace-bin", MetadataValue::from_bytes(b"hello"));
+
+        // when
+        let pairs = metadata.to_pairs();
+
+        // then
+        let expected = vec![
+            ("x-request-id".to_string(), "42".to_string()),
+            ("trace-bin".to_string(), "aGVsbG8".to_string()),
+        ];
+        assert_eq!(pairs, expected);
+    }
 }
@@ -1,16 +1,21 @@
 #![allow(clippy::module_name_repetitions)]
+pub(crate) mod embedded;
 pub mod message;
 pub mod metadata;
+pub mod raw;
 pub mod request;
 pub mod response;
+pub mod status;
 
 pub use message::DynamicMessage;
 pub use request::RequestMessage;
 pub use response::ResponseMessage;
 
+use crate::client::{connection::ConnectionConfig, transport::Transport, GrpcClient};
 use crate::error::Error;
 use crate::Result;
 use prost_reflect::{DescriptorPool, MessageDescriptor, MethodDescriptor, ServiceDescriptor};
+use prost_types::FileDescriptorProto;
 use std::path::Path;
 
 #[derive(Default, Debug, Clone)]
@@ -39,10 +44,88 @@ impl ProtoDescriptor {
         includes: impl IntoIterator<Item = impl AsRef<Path>>,
         files: impl IntoIterator<Item = impl AsRef<Path>>,
     ) -> Result<Self> {
+        let mut desc = Self::default();
+        desc.add_files(includes, files)?;
+        Ok(desc)
+    }
+
+    /// Compiles proto files and adds them to the `DescriptorPool`, e.g.
+    /// to extend the protos loaded via server reflection.
+    ///
+    /// # Errors
+    /// - Failed to compile proto `ProtoxCompileError`
+    /// - Failed to generate descriptor `DescriptorError`
+    pub fn add_files(
+        &mut self,
+        includes: impl IntoIterator<Item = impl AsRef<Path>>,
+        files: impl IntoIterator<Item = impl AsRef<Path>>,
+    ) -> Result<()> {
         // Compile proto files to file descriptors
         let file_desc_set = protox::compile(files, includes).map_err(Error::ProtoxCompileError)?;
-        // Generate descriptor pool from file descriptor
-        let pool = DescriptorPool::from_file_descriptor_set(file_desc_set)
+        // Add the file descriptors to the descriptor pool
+        self.pool
+            .add_file_descriptor_set(file_desc_set)
+            .map_err(Error::DescriptorError)
+    }
+
+    /// Instantiate `DescriptorPool` from binary encoded `FileDescriptorSet`
+    /// files, such as generated by `buf build` or `protoc --descriptor_set_out`.
+    ///
+    /// # Errors
+    /// - Failed to read a file `ReadDescriptorSetError`
+    /// - Failed to decode or generate descriptor `DescriptorError`
+    pub fn from_descriptor_sets(files: impl IntoIterator<Item = impl AsRef<Path>>) -> Result<Self> {
+        let mut desc = Self::default();
+        desc.add_descriptor_sets(files)?;
+        Ok(desc)
+    }
+
+    /// Adds binary encoded `FileDescriptorSet` files to the `DescriptorPool`.
+    /// Files that are already in the pool are skipped.
+    ///
+    /// # Errors
+    /// - Failed to read a file `ReadDescriptorSetError`
+    /// - Failed to decode or generate descriptor `DescriptorError`
+    pub fn add_descriptor_sets(
+        &mut self,
+        files: impl IntoIterator<Item = impl AsRef<Path>>,
+    ) -> Result<()> {
+        for file in files {
+            let bytes = std::fs::read(file).map_err(Error::ReadDescriptorSetError)?;
+            self.pool
+                .decode_file_descriptor_set(bytes.as_slice())
+                .map_err(Error::DescriptorError)?;
+        }
+        Ok(())
+    }
+
+    /// Instantiate `DescriptorPool` from the server reflection service of
+    /// a `gRPC` server. The files of all listed services are loaded including
+    /// their dependencies.
+    ///
+    /// # Errors
+    /// - Failed to parse the address
+    /// - Failed to load the file descriptors `ReflectionError`
+    /// - Failed to generate descriptor `DescriptorError`
+    pub async fn from_reflection(
+        address: &str,
+        connection: Option<ConnectionConfig>,
+    ) -> Result<Self> {
+        let mut client = GrpcClient::from_address(address, connection, Transport::Grpc)?;
+        let files = client.reflect_file_descriptors().await?;
+        Self::from_file_descriptor_protos(files)
+    }
+
+    /// Instantiate `DescriptorPool` from file descriptors. The files may
+    /// be given in any order.
+    ///
+    /// # Errors
+    /// - Failed to generate descriptor `DescriptorError`
+    pub fn from_file_descriptor_protos(
+        files: impl IntoIterator<Item = FileDescriptorProto>,
+    ) -> Result<Self> {
+        let mut pool = DescriptorPool::new();
+        pool.add_file_descriptor_protos(files)
             .map_err(Error::DescriptorError)?;
         Ok(Self { pool })
     }
@@ -110,3 +193,52 @@ impl ProtoDescriptor {
         ResponseMessage::new(self.get_response_descriptor(method), method.clone())
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use prost::Message;
+
+    #[test]
+    fn test_from_descriptor_sets() {
+        // given
+        let file_desc_set = protox::compile(["test_files/test.proto"], ["."]).unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("test.protoset");
+        std::fs::write(&path, file_desc_set.encode_to_vec()).unwrap();
+
+        // when
+        let desc = ProtoDescriptor::from_descriptor_sets([&path]).unwrap();
+
+        // then
+        assert!(desc
+            .get_method_by_name("proto.TestService", "Simple")
+            .is_some());
+    }
+
+    #[test]
+    fn test_add_files() {
+        // given
+        let mut desc = ProtoDescriptor::new(["test_files"], ["mock.proto"]).unwrap();
+
+        // when
+        desc.add_files(["test_files"], ["test.proto"]).unwrap();
+
+        // then
+        assert!(desc
+            .get_method_by_name("mock.Greeter", "SayHello")
+            .is_some());
+        assert!(desc
+            .get_method_by_name("proto.TestService", "Simple")
+            .is_some());
+    }
+
+    #[test]
+    fn test_from_descriptor_sets_missing_file() {
+        // when
+        let result = ProtoDescriptor::from_descriptor_sets(["test_files/missing.protoset"]);
+
+        // then
+        assert!(matches!(result, Err(Error::ReadDescriptorSetError(_))));
+    }
+}
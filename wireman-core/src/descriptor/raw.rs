@@ -0,0 +1,103 @@
+//! The text encodings of raw protobuf payloads.
+use crate::descriptor::metadata::BASE64;
+use crate::error::Error;
+use crate::Result;
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// The text encoding of a raw protobuf payload.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RawFormat {
+    /// Hexadecimal digits, e.g. `0a0568656c6c6f`.
+    Hex,
+    /// Standard base64, e.g. `CgVoZWxsbw==`.
+    Base64,
+}
+
+impl RawFormat {
+    /// Decodes a payload. Whitespace is ignored.
+    ///
+    /// # Errors
+    /// - The text is not valid in the format
+    pub fn decode(self, text: &str) -> Result<Vec<u8>> {
+        let text: String = text.split_whitespace().collect();
+        match self {
+            Self::Hex => decode_hex(&text),
+            Self::Base64 => BASE64
+                .decode(text)
+                .map_err(|err| Error::InvalidRawPayload(err.to_string())),
+        }
+    }
+
+    /// Encodes a payload.
+    #[must_use]
+    pub fn encode(self, bytes: &[u8]) -> String {
+        match self {
+            Self::Hex => bytes.iter().map(|byte| format!("{byte:02x}")).collect(),
+            Self::Base64 => BASE64.encode(bytes),
+        }
+    }
+}
+
+impl fmt::Display for RawFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Hex => write!(f, "Hex"),
+            Self::Base64 => write!(f, "Base64"),
+        }
+    }
+}
+
+/// Decodes a string of hexadecimal digits.
+fn decode_hex(text: &str) -> Result<Vec<u8>> {
+    text.as_bytes()
+        .chunks(2)
+        .enumerate()
+        .map(|(i, digits)| {
+            std::str::from_utf8(digits)
+                .ok()
+                .filter(|digits| digits.len() == 2 && digits.chars().all(|c| c.is_ascii_hexdigit()))
+                .and_then(|digits| u8::from_str_radix(digits, 16).ok())
+                .ok_or_else(|| Error::InvalidRawPayload(format!("invalid hex digits at {}", i * 2)))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_decode() {
+        // given
+        let hex = "0a05 6865\n6c6c6f";
+        let base64 = "CgVoZWxsbw";
+
+        // when
+        let from_hex = RawFormat::Hex.decode(hex).unwrap();
+        let from_base64 = RawFormat::Base64.decode(base64).unwrap();
+
+        // then
+        assert_eq!(from_hex, b"\n\x05hello");
+        assert_eq!(from_base64, b"\n\x05hello");
+        assert!(RawFormat::Hex.decode("0a0").is_err());
+        assert!(RawFormat::Hex.decode("zz").is_err());
+        assert!(RawFormat::Hex.decode("+f").is_err());
+    }
+
+    #[test]
+    fn test_encode() {
+        // given
+        let bytes = b"\n\x05hello";
+
+        // when
+        let hex = RawFormat::Hex.encode(bytes);
+        let base64 = RawFormat::Base64.encode(bytes);
+
+        // then
+        assert_eq!(hex, "0a0568656c6c6f");
+        assert_eq!(base64, "CgVoZWxsbw==");
+    }
+}
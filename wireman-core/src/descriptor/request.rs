@@ -1,16 +1,17 @@
 use super::{metadata::Metadata, DynamicMessage};
 use crate::{
+    client::{compression::Compression, transport::Transport},
     error::{Error, FROM_UTF8},
     Result,
 };
+use bytes::Bytes;
 use http::uri::PathAndQuery;
+use prost_reflect::prost::Message;
 use prost_reflect::{MessageDescriptor, MethodDescriptor};
 use serde::{ser::SerializeStruct, Serialize, Serializer};
 use std::str::FromStr;
-use tonic::{
-    metadata::{Ascii, MetadataKey, MetadataValue},
-    Request,
-};
+use std::time::Duration;
+use tonic::Request;
 
 /// Holds all the necessary data for a `gRPC` request, including
 /// the message, method descriptor, and optional metadata.
@@ -24,6 +25,15 @@ pub struct RequestMessage {
     metadata: Option<Metadata>,
     /// The host address.
     address: String,
+    /// The deadline of the call, relative to its start.
+    timeout: Option<Duration>,
+    /// The protocol with which the call is sent.
+    transport: Transport,
+    /// The compression of the messages.
+    compression: Compression,
+    /// A raw protobuf payload which is sent verbatim instead of
+    /// the message, e.g. with unknown fields.
+    raw: Option<Bytes>,
 }
 
 impl RequestMessage {
@@ -37,6 +47,10 @@ impl RequestMessage {
             method_desc,
             metadata: None,
             address: String::new(),
+            timeout: None,
+            transport: Transport::default(),
+            compression: Compression::default(),
+            raw: None,
         }
     }
 
@@ -86,23 +100,77 @@ impl RequestMessage {
         self.address = address.to_string();
     }
 
+    /// Get the timeout of the call.
+    #[must_use]
+    pub fn timeout(&self) -> Option<Duration> {
+        self.timeout
+    }
+
+    /// Sets the timeout of the call. It is sent to the server as
+    /// `grpc-timeout` header and enforced by the client.
+    pub fn set_timeout(&mut self, timeout: Option<Duration>) {
+        self.timeout = timeout;
+    }
+
+    /// Get the protocol with which the call is sent.
+    #[must_use]
+    pub fn transport(&self) -> Transport {
+        self.transport
+    }
+
+    /// Sets the protocol with which the call is sent.
+    pub fn set_transport(&mut self, transport: Transport) {
+        self.transport = transport;
+    }
+
+    /// Get the compression of the messages.
+    #[must_use]
+    pub fn compression(&self) -> Compression {
+        self.compression
+    }
+
+    /// Sets the compression of the messages.
+    pub fn set_compression(&mut self, compression: Compression) {
+        self.compression = compression;
+    }
+
+    /// Get the encoded size of the message in bytes, i.e. the size
+    /// of the raw payload if set.
+    #[must_use]
+    pub fn encoded_len(&self) -> usize {
+        self.raw
+            .as_ref()
+            .map_or_else(|| self.message().encoded_len(), Bytes::len)
+    }
+
+    /// Get the raw protobuf payload, if any.
+    #[must_use]
+    pub fn raw(&self) -> Option<&Bytes> {
+        self.raw.as_ref()
+    }
+
+    /// Sets a raw protobuf payload. It is sent verbatim instead of
+    /// the message.
+    pub fn set_raw(&mut self, raw: Option<Bytes>) {
+        self.raw = raw;
+    }
+
     /// Get the metadata associated with the request.
     #[must_use]
     pub fn metadata(&self) -> &Option<Metadata> {
         &self.metadata
     }
 
-    /// Insert metadata into the request.
+    /// Insert metadata into the request. Values of `-bin` keys are
+    /// base64 decoded and sent as binary metadata.
     ///
     /// # Errors
     ///
-    /// - Failed to parse metadata value/key to ascii
+    /// - Failed to parse the metadata key or value
     pub fn insert_metadata(&mut self, key: &str, val: &str) -> Result<()> {
-        let key: MetadataKey<Ascii> = key.parse().map_err(|_| Error::ParseToAsciiError)?;
-        let val: MetadataValue<Ascii> = val.parse().map_err(|_| Error::ParseToAsciiError)?;
-        let map = self.metadata.get_or_insert(Metadata::new());
-        map.insert(key, val);
-        Ok(())
+        self.metadata
+            .get_or_insert(Metadata::new())
+            .insert_str(key, val)
     }
 
     /// Get the URI path for `gRPC` calls based on the method descriptor.
@@ -124,10 +192,14 @@ impl RequestMessage {
     #[must_use]
     pub fn into_request(self) -> Request<RequestMessage> {
         let metadata = self.metadata().clone();
+        let timeout = self.timeout();
         let mut req = Request::new(self);
         if let Some(meta) = metadata {
             *req.metadata_mut() = meta.inner;
         }
+        if let Some(timeout) = timeout {
+            req.set_timeout(timeout);
+        }
         req
     }
 
@@ -202,6 +274,20 @@ mod test {
         assert_eq!(given_req.get_ref().message_descriptor(), message_descriptor);
     }
 
+    #[test]
+    fn test_into_request_with_timeout() {
+        // given
+        let mut given_message = load_test_message("Simple");
+        given_message.set_timeout(Some(Duration::from_millis(1500)));
+
+        // when
+        let given_req = given_message.into_request();
+
+        // then
+        let metadata = given_req.metadata();
+        assert_eq!(metadata.get("grpc-timeout").unwrap(), "1500000u");
+    }
+
     #[test]
     fn test_to_json() {
         // given
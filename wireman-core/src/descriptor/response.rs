@@ -1,4 +1,7 @@
-use super::DynamicMessage;
+use super::{metadata::Metadata, DynamicMessage};
+use crate::client::metrics::Metrics;
+use bytes::Bytes;
+use prost_reflect::prost::Message;
 use prost_reflect::{MessageDescriptor, MethodDescriptor};
 
 /// Holds all the necessary data for a `gRPC` response.
@@ -6,6 +9,16 @@ use prost_reflect::{MessageDescriptor, MethodDescriptor};
 pub struct ResponseMessage {
     pub message: DynamicMessage,
     method_desc: MethodDescriptor,
+    /// The initial metadata sent by the server.
+    headers: Metadata,
+    /// The trailing metadata sent by the server.
+    trailers: Metadata,
+    /// The number of attempts of the call, including the retries.
+    attempts: u32,
+    /// The protobuf encoding of the message as received.
+    raw: Bytes,
+    /// The timings and message sizes of the call.
+    metrics: Metrics,
 }
 
 impl ResponseMessage {
@@ -16,6 +29,11 @@ impl ResponseMessage {
         Self {
             message,
             method_desc,
+            headers: Metadata::new(),
+            trailers: Metadata::new(),
+            attempts: 1,
+            raw: Bytes::new(),
+            metrics: Metrics::default(),
         }
     }
 
@@ -23,4 +41,71 @@ impl ResponseMessage {
     pub fn set_message(&mut self, message: DynamicMessage) {
         self.message = message;
     }
+
+    /// Get the protobuf encoding of the message as received. Holds
+    /// the unknown fields which are not part of the decoded message.
+    /// Empty for the json encoding of the Connect protocol.
+    #[must_use]
+    pub fn raw(&self) -> &Bytes {
+        &self.raw
+    }
+
+    /// Sets the protobuf encoding of the message as received.
+    pub fn set_raw(&mut self, raw: Bytes) {
+        self.raw = raw;
+    }
+
+    /// Get the encoded size of the message in bytes.
+    #[must_use]
+    pub fn encoded_len(&self) -> usize {
+        if self.raw.is_empty() {
+            self.message.encoded_len()
+        } else {
+            self.raw.len()
+        }
+    }
+
+    /// Get the timings and message sizes of the call.
+    #[must_use]
+    pub fn metrics(&self) -> &Metrics {
+        &self.metrics
+    }
+
+    /// Sets the timings and message sizes of the call.
+    pub fn set_metrics(&mut self, metrics: Metrics) {
+        self.metrics = metrics;
+    }
+
+    /// Get the number of attempts of the call, including the retries.
+    #[must_use]
+    pub fn attempts(&self) -> u32 {
+        self.attempts
+    }
+
+    /// Sets the number of attempts of the call.
+    pub fn set_attempts(&mut self, attempts: u32) {
+        self.attempts = attempts;
+    }
+
+    /// Get the initial metadata of the response.
+    #[must_use]
+    pub fn headers(&self) -> &Metadata {
+        &self.headers
+    }
+
+    /// Sets the initial metadata of the response.
+    pub fn set_headers(&mut self, headers: Metadata) {
+        self.headers = headers;
+    }
+
+    /// Get the trailing metadata of the response.
+    #[must_use]
+    pub fn trailers(&self) -> &Metadata {
+        &self.trailers
+    }
+
+    /// Sets the trailing metadata of the response.
+    pub fn set_trailers(&mut self, trailers: Metadata) {
+        self.trailers = trailers;
+    }
 }
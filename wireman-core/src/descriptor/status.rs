@@ -0,0 +1,188 @@
+//! Decoding of the rich error model which servers send as binary
+//! encoded `google.rpc.Status` in the `grpc-status-details-bin` trailer.
+use super::embedded;
+use super::metadata::BASE64;
+use crate::error::Error;
+use crate::Result;
+use base64::Engine;
+use prost::Message;
+use prost_reflect::{DescriptorPool, DynamicMessage};
+use prost_types::Any;
+use serde_json::{json, Value};
+use std::sync::OnceLock;
+
+/// The embedded proto files of the `google.rpc` error model.
+const ERROR_MODEL_FILES: [&str; 2] = ["google/rpc/status.proto", "google/rpc/error_details.proto"];
+
+/// The `google.rpc.Status` message.
+#[derive(Clone, PartialEq, Message)]
+pub(crate) struct RpcStatus {
+    #[prost(int32, tag = "1")]
+    pub(crate) code: i32,
+    #[prost(string, tag = "2")]
+    pub(crate) message: String,
+    #[prost(message, repeated, tag = "3")]
+    pub(crate) details: Vec<Any>,
+}
+
+/// Returns the descriptor pool of the `google.rpc` error model. It
+/// holds the standard error details such as `BadRequest`, `ErrorInfo`
+/// and `RetryInfo`. The pool is compiled once.
+fn error_model() -> Option<&'static DescriptorPool> {
+    static POOL: OnceLock<Option<DescriptorPool>> = OnceLock::new();
+    POOL.get_or_init(|| embedded::compile(&ERROR_MODEL_FILES).ok())
+        .as_ref()
+}
+
+/// Decodes a binary encoded `google.rpc.Status` into json. The messages
+/// packed in its `details` are looked up in the given pool first and
+/// then in the standard error details. Details of an unknown type or
+/// which fail to decode are returned with their base64 encoded value.
+///
+/// # Errors
+/// - Failed to decode the status `DecodeStatusDetailsError`
+pub fn decode_status_details(bytes: &[u8], pool: Option<&DescriptorPool>) -> Result<Value> {
+    let status = RpcStatus::decode(bytes).map_err(Error::DecodeStatusDetailsError)?;
+    Ok(status_to_json(&status, pool))
+}
+
+/// Returns the json of a `google.rpc.Status`. The messages packed in
+/// its `details` are resolved as in [`decode_status_details`].
+pub(crate) fn status_to_json(status: &RpcStatus, pool: Option<&DescriptorPool>) -> Value {
+    let details = status
+        .details
+        .iter()
+        .map(|any| decode_any(any, pool))
+        .collect();
+    status_json(status.code, &status.message, details)
+}
+
+/// Returns the json of a `google.rpc.Status` with the given details.
+pub(crate) fn status_json(code: i32, message: &str, details: Vec<Value>) -> Value {
+    json!({
+        "code": code,
+        "message": message,
+        "details": details,
+    })
+}
+
+/// Decodes a single `google.protobuf.Any` into json. The type url is
+/// kept in the `@type` field as in the canonical json mapping. Falls
+/// back to the base64 encoded value if the type is unknown or the
+/// value fails to decode.
+pub(crate) fn decode_any(any: &Any, pool: Option<&DescriptorPool>) -> Value {
+    let name = any.type_url.rsplit('/').next().unwrap_or_default();
+    let desc = pool
+        .and_then(|pool| pool.get_message_by_name(name))
+        .or_else(|| error_model().and_then(|pool| pool.get_message_by_name(name)));
+    let value = desc.and_then(|desc| {
+        let message = DynamicMessage::decode(desc, any.value.as_slice()).ok()?;
+        serde_json::to_value(&message).ok()
+    });
+    match value {
+        Some(Value::Object(mut fields)) => {
+            fields.insert(String::from("@type"), Value::String(any.type_url.clone()));
+            Value::Object(fields)
+        }
+        // Well-known types are mapped to a json value
+        Some(value) => json!({
+            "@type": any.type_url,
+            "value": value,
+        }),
+        None => json!({
+            "@type": any.type_url,
+            "value": BASE64.encode(&any.value),
+        }),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::ProtoDescriptor;
+
+    /// Encodes a message from json as `google.protobuf.Any`.
+    fn pack(pool: &DescriptorPool, name: &str, json: &str) -> Any {
+        let desc = pool.get_message_by_name(name).unwrap();
+        let mut de = serde_json::Deserializer::from_str(json);
+        let message = DynamicMessage::deserialize(desc, &mut de).unwrap();
+        Any {
+            type_url: format!("type.googleapis.com/{name}"),
+            value: message.encode_to_vec(),
+        }
+    }
+
+    #[test]
+    fn test_decode_status_details() {
+        // given
+        let desc = ProtoDescriptor::new(vec!["."], vec!["test_files/test.proto"]).unwrap();
+        let pool = desc.get_message_by_name("proto.SimpleReq").unwrap();
+        let pool = pool.parent_pool();
+        let status = RpcStatus {
+            code: 3,
+            message: String::from("invalid request"),
+            details: vec![
+                pack(
+                    error_model().unwrap(),
+                    "google.rpc.BadRequest",
+                    r#"{"fieldViolations":[{"field":"number","description":"too small"}]}"#,
+                ),
+                pack(
+                    error_model().unwrap(),
+                    "google.rpc.RetryInfo",
+                    r#"{"retryDelay":"1.500s"}"#,
+                ),
+                pack(pool, "proto.SimpleReq", r#"{"number":1}"#),
+                Any {
+                    type_url: String::from("type.googleapis.com/unknown.Type"),
+                    value: vec![1, 2, 3],
+                },
+                Any {
+                    type_url: String::from("type.googleapis.com/google.rpc.RetryInfo"),
+                    value: vec![0xff, 0xff],
+                },
+            ],
+        };
+
+        // when
+        let json = decode_status_details(&status.encode_to_vec(), Some(pool)).unwrap();
+
+        // then
+        let expected = json!({
+            "code": 3,
+            "message": "invalid request",
+            "details": [
+                {
+                    "@type": "type.googleapis.com/google.rpc.BadRequest",
+                    "fieldViolations": [{"field": "number", "description": "too small"}],
+                },
+                {
+                    "@type": "type.googleapis.com/google.rpc.RetryInfo",
+                    "retryDelay": "1.500s",
+                },
+                {
+                    "@type": "type.googleapis.com/proto.SimpleReq",
+                    "number": 1,
+                },
+                {
+                    "@type": "type.googleapis.com/unknown.Type",
+                    "value": "AQID",
+                },
+                {
+                    "@type": "type.googleapis.com/google.rpc.RetryInfo",
+                    "value": "//8=",
+                },
+            ],
+        });
+        assert_eq!(json, expected);
+    }
+
+    #[test]
+    fn test_decode_status_details_invalid() {
+        // when
+        let result = decode_status_details(&[0xff, 0xff], None);
+
+        // then
+        assert!(matches!(result, Err(Error::DecodeStatusDetailsError(_))));
+    }
+}
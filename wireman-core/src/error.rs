@@ -1,6 +1,10 @@
 #![allow(clippy::module_name_repetitions, clippy::enum_variant_names)]
-use prost_reflect::DescriptorError;
+use crate::client::transport::{Transport, TransportError};
+use crate::descriptor::metadata::Metadata;
+use crate::descriptor::status::decode_status_details;
+use prost_reflect::{DescriptorError, DescriptorPool};
 use thiserror::Error as ThisError;
+pub use tonic::Code;
 
 /// The result type for this library
 pub type Result<T> = std::result::Result<T, Error>;
@@ -24,22 +28,74 @@ pub enum Error {
     #[error("error generating the descriptor pool")]
     DescriptorError(#[source] DescriptorError),
 
+    /// Failed to read a file descriptor set
+    #[error("error reading file descriptor set")]
+    ReadDescriptorSetError(#[source] std::io::Error),
+
+    /// Failed to load the file descriptors via server reflection
+    #[error("server reflection: {0}")]
+    ReflectionError(String),
+
+    /// Failed to decode a file descriptor
+    #[error("error decoding file descriptor")]
+    DecodeFileDescriptorError(#[source] prost::DecodeError),
+
     /// Failed to create a grpc channel
     #[error("error creating grpc channel")]
     GrpcChannelCreateError(#[source] protox::Error),
 
     /// Grpc channel is not ready
     #[error("grpc channel is not ready: {0}")]
-    GrpcNotReady(#[from] tonic::transport::Error),
+    GrpcNotReady(#[source] TransportError),
+
+    /// The transport does not support client or bidirectional streaming
+    #[error("{0} does not support client streaming")]
+    UnsupportedStreaming(Transport),
+
+    /// The response of a Connect call violates the protocol
+    #[error("connect protocol: {0}")]
+    ConnectProtocolError(String),
 
     /// Failed to make a unary grpc call
     #[error("grpc: {0}")]
-    GrpcError(GrpcStatus),
+    GrpcError(Box<GrpcStatus>),
+
+    /// The deadline of the call exceeded before it completed
+    #[error("deadline exceeded after {0:?}")]
+    DeadlineExceeded(std::time::Duration),
 
     /// Failed to load the custom TLS certificate
     #[error("failed to load custom TLS certificate")]
     LoadTLSCertificateError(#[source] std::io::Error),
 
+    /// Failed to load the TLS client certificate
+    #[error("failed to load TLS client certificate")]
+    LoadTLSClientCertError(#[source] std::io::Error),
+
+    /// Failed to load the TLS client key
+    #[error("failed to load TLS client key")]
+    LoadTLSClientKeyError(#[source] std::io::Error),
+
+    /// The TLS client key file contains no private key
+    #[error("no private key found in TLS client key file")]
+    NoTLSClientKey,
+
+    /// Only one of TLS client certificate and key is given
+    #[error("TLS client certificate and key must be given together")]
+    IncompleteTLSClientAuth,
+
+    /// The TLS client certificate or key is invalid
+    #[error("invalid TLS client certificate or key: {0}")]
+    TLSClientAuthError(#[source] rustls::Error),
+
+    /// The `:authority` override is not a valid authority
+    #[error("invalid authority: {0}")]
+    InvalidAuthority(String),
+
+    /// The proxy uri is invalid
+    #[error("invalid proxy: {0}")]
+    InvalidProxy(String),
+
     /// Failed to serialize proto messages
     #[error("failed to serialize proto message")]
     SerializeJsonError(#[source] serde_json::Error),
@@ -48,14 +104,101 @@ pub enum Error {
     #[error("failed to serialize the message")]
     SerializeMessageError(String),
 
+    /// Failed to read the service config file
+    #[error("error reading service config")]
+    ReadServiceConfigError(#[source] std::io::Error),
+
+    /// The service config is invalid
+    #[error("invalid service config: {0}")]
+    ServiceConfigError(String),
+
+    /// Failed to read a mock rules file
+    #[error("error reading mock rules")]
+    ReadMockRulesError(#[source] std::io::Error),
+
+    /// A mock rule is invalid
+    #[error("invalid mock rule: {0}")]
+    MockRuleError(String),
+
+    /// The mock server failed
+    #[error("mock server: {0}")]
+    MockServerError(String),
+
+    /// Failed to decode the rich error details of a grpc status
+    #[error("failed to decode grpc status details")]
+    DecodeStatusDetailsError(#[source] prost::DecodeError),
+
     /// Failed to parse to ascii
     #[error("error parsing to ascii")]
     ParseToAsciiError,
+
+    /// A raw protobuf payload is not valid hex or base64
+    #[error("invalid raw payload: {0}")]
+    InvalidRawPayload(String),
+
+    /// A metadata header has an invalid key or value
+    #[error("invalid header {0}: {1}")]
+    InvalidMetadata(String, String),
+}
+
+impl Error {
+    /// Whether the call was cancelled because its deadline exceeded,
+    /// either by the client or by the server.
+    #[must_use]
+    pub fn is_deadline_exceeded(&self) -> bool {
+        match self {
+            Self::DeadlineExceeded(_) => true,
+            Self::GrpcError(status) => status.code == tonic::Code::DeadlineExceeded,
+            _ => false,
+        }
+    }
+
+    /// Whether the call failed because the connection to the server
+    /// could not be established or broke.
+    #[must_use]
+    pub fn is_unavailable(&self) -> bool {
+        match self {
+            Self::GrpcNotReady(_) => true,
+            Self::GrpcError(status) => status.code == tonic::Code::Unavailable,
+            _ => false,
+        }
+    }
+
+    /// Sets the headers of the response on a failed call, e.g. if the
+    /// status was received after the headers of a stream.
+    #[must_use]
+    pub(crate) fn with_headers(self, headers: &Metadata) -> Self {
+        match self {
+            Self::GrpcError(mut status) => {
+                status.headers = headers.clone();
+                Self::GrpcError(status)
+            }
+            err => err,
+        }
+    }
+
+    /// Returns the `gRPC` status code of the failed call. Errors which
+    /// occurred before the call was sent are `Unknown`.
+    #[must_use]
+    pub fn code(&self) -> tonic::Code {
+        match self {
+            Self::GrpcError(status) => status.code,
+            Self::DeadlineExceeded(_) => tonic::Code::DeadlineExceeded,
+            Self::GrpcNotReady(_) => tonic::Code::Unavailable,
+            _ => tonic::Code::Unknown,
+        }
+    }
 }
 
 impl From<tonic::Status> for Error {
     fn from(status: tonic::Status) -> Self {
-        Self::GrpcError(status.into())
+        Self::GrpcError(Box::new(status.into()))
+    }
+}
+
+impl From<GrpcStatus> for Error {
+    fn from(status: GrpcStatus) -> Self {
+        Self::GrpcError(Box::new(status))
     }
 }
 
@@ -66,6 +209,45 @@ pub struct GrpcStatus {
     pub code: tonic::Code,
     /// The error message
     pub message: String,
+    /// The rich error details as json, decoded from the
+    /// `google.rpc.Status` sent in `grpc-status-details-bin`
+    pub details: Option<serde_json::Value>,
+    /// The headers sent by the server before the status. Empty if the
+    /// server responded with the status only.
+    pub headers: Metadata,
+    /// The trailers sent by the server with the status
+    pub trailers: Metadata,
+}
+
+impl GrpcStatus {
+    /// Returns a status without details and metadata.
+    #[must_use]
+    pub fn new<T: Into<String>>(code: tonic::Code, message: T) -> Self {
+        Self {
+            code,
+            message: message.into(),
+            details: None,
+            headers: Metadata::default(),
+            trailers: Metadata::default(),
+        }
+    }
+
+    /// Returns the status of a grpc call. The types of the rich error
+    /// details are looked up in the given pool and in the standard
+    /// `google.rpc` error details. Details that fail to decode are kept
+    /// with their type url and raw value. The metadata of the status
+    /// are its trailers.
+    #[must_use]
+    pub fn from_status(status: &tonic::Status, pool: Option<&DescriptorPool>) -> Self {
+        let details = Some(status.details())
+            .filter(|details| !details.is_empty())
+            .and_then(|details| decode_status_details(details, pool).ok());
+        Self {
+            details,
+            trailers: status.metadata().clone().into(),
+            ..Self::new(status.code(), status.message())
+        }
+    }
 }
 
 impl std::fmt::Display for GrpcStatus {
@@ -76,11 +258,61 @@ impl std::fmt::Display for GrpcStatus {
 
 impl From<tonic::Status> for GrpcStatus {
     fn from(status: tonic::Status) -> Self {
-        GrpcStatus {
-            code: status.code(),
-            message: status.message().to_owned(),
-        }
+        Self::from_status(&status, None)
     }
 }
 
+/// Maps the name of a status code in lower snake case, e.g. `not_found`,
+/// as sent by the Connect protocol and used in service configs. Accepts
+/// both spellings `canceled` and `cancelled`.
+pub(crate) fn code_from_str(code: &str) -> Option<Code> {
+    let code = match code {
+        "ok" => Code::Ok,
+        "canceled" | "cancelled" => Code::Cancelled,
+        "unknown" => Code::Unknown,
+        "invalid_argument" => Code::InvalidArgument,
+        "deadline_exceeded" => Code::DeadlineExceeded,
+        "not_found" => Code::NotFound,
+        "already_exists" => Code::AlreadyExists,
+        "permission_denied" => Code::PermissionDenied,
+        "resource_exhausted" => Code::ResourceExhausted,
+        "failed_precondition" => Code::FailedPrecondition,
+        "aborted" => Code::Aborted,
+        "out_of_range" => Code::OutOfRange,
+        "unimplemented" => Code::Unimplemented,
+        "internal" => Code::Internal,
+        "unavailable" => Code::Unavailable,
+        "data_loss" => Code::DataLoss,
+        "unauthenticated" => Code::Unauthenticated,
+        _ => return None,
+    };
+    Some(code)
+}
+
 pub const FROM_UTF8: &str = "From UTF8 error";
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_code_from_str() {
+        // given
+        let names = ["ok", "canceled", "cancelled", "data_loss", "NOT_FOUND"];
+
+        // when
+        let codes: Vec<_> = names.iter().map(|name| code_from_str(name)).collect();
+
+        // then
+        assert_eq!(
+            codes,
+            vec![
+                Some(Code::Ok),
+                Some(Code::Cancelled),
+                Some(Code::Cancelled),
+                Some(Code::DataLoss),
+                None,
+            ]
+        );
+    }
+}
@@ -0,0 +1,541 @@
+//! Load testing of a method. The request is sent repeatedly by a
+//! number of concurrent workers, which share the connection of the
+//! client, and the latencies and status codes of the calls are
+//! collected into a [`BenchReport`].
+use crate::client::metrics::millis;
+use crate::client::GrpcClient;
+use crate::descriptor::RequestMessage;
+use crate::error::{Code, Error};
+use crate::Result;
+use hdrhistogram::Histogram;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Mutex;
+use tokio::time::{Instant, Interval, MissedTickBehavior};
+
+/// The number of buckets of the latency histogram.
+const HISTOGRAM_BUCKETS: u64 = 10;
+
+/// The percentiles of the latency distribution.
+const PERCENTILES: [f64; 8] = [10.0, 25.0, 50.0, 75.0, 90.0, 95.0, 99.0, 99.9];
+
+/// The largest latency which is distinguished in microseconds. Slower
+/// calls are recorded with this latency.
+const MAX_LATENCY: u64 = 3_600_000_000;
+
+/// The width of the largest bar of the text histogram.
+const BAR_WIDTH: u64 = 40;
+
+/// When a benchmark stops sending requests.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BenchLimit {
+    /// Stops after the number of requests.
+    Requests(u64),
+    /// Stops after the duration. Calls in flight are completed.
+    Duration(Duration),
+}
+
+/// The settings of a benchmark.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BenchConfig {
+    /// When the benchmark stops.
+    pub limit: BenchLimit,
+    /// The number of workers which send requests concurrently.
+    pub concurrency: usize,
+    /// The maximum number of requests per second of all workers
+    /// together. Unlimited if `None`.
+    pub rate: Option<f64>,
+}
+
+impl Default for BenchConfig {
+    fn default() -> Self {
+        Self {
+            limit: BenchLimit::Requests(200),
+            concurrency: 50,
+            rate: None,
+        }
+    }
+}
+
+/// The result of a benchmark. Latencies are serialized as fractional
+/// milliseconds.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct BenchReport {
+    /// The number of calls.
+    pub count: u64,
+    /// The time from the first request until the last response.
+    #[serde(with = "millis")]
+    pub total: Duration,
+    /// The number of calls per second.
+    pub rps: f64,
+    /// The fastest call.
+    #[serde(with = "millis")]
+    pub fastest: Duration,
+    /// The slowest call.
+    #[serde(with = "millis")]
+    pub slowest: Duration,
+    /// The mean latency of the calls.
+    #[serde(with = "millis")]
+    pub average: Duration,
+    /// The latency histogram, in equally sized buckets between the
+    /// fastest and the slowest call.
+    pub histogram: Vec<Bucket>,
+    /// The latency percentiles.
+    pub latency_distribution: Vec<Percentile>,
+    /// The number of calls by their status code, e.g. `Ok` or
+    /// `Unavailable`.
+    pub status_code_distribution: BTreeMap<String, u64>,
+    /// The number of failed calls by their error message.
+    pub error_distribution: BTreeMap<String, u64>,
+}
+
+/// A bucket of the latency histogram.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Bucket {
+    /// The upper bound of the bucket.
+    #[serde(with = "millis")]
+    pub mark: Duration,
+    /// The number of calls in the bucket.
+    pub count: u64,
+}
+
+/// A percentile of the latency distribution.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Percentile {
+    /// The percentile, e.g. `99.9`.
+    pub percentile: f64,
+    /// The latency of the percentile.
+    #[serde(with = "millis")]
+    pub latency: Duration,
+}
+
+/// Benchmarks the method of the requests with the given client.
+///
+/// Unary and server streaming calls send the first request. Client
+/// and bidirectional streaming calls send all requests in order. The
+/// latency of a streaming call is the time until the stream closed.
+///
+/// # Errors
+/// - No request message is given
+/// - The rate is not a positive number
+pub async fn bench(
+    client: GrpcClient,
+    reqs: Vec<RequestMessage>,
+    config: BenchConfig,
+) -> Result<BenchReport> {
+    if reqs.is_empty() {
+        return Err(Error::Internal(String::from("No request messages")));
+    }
+    let rate = match config.rate {
+        Some(rate) if !rate.is_finite() || rate <= 0.0 => {
+            return Err(Error::Internal(format!("Invalid rate: {rate}")));
+        }
+        Some(rate) => {
+            let mut interval = tokio::time::interval(Duration::from_secs_f64(1.0 / rate));
+            interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+            Some(Arc::new(Mutex::new(interval)))
+        }
+        None => None,
+    };
+
+    let start = Instant::now();
+    let schedule = Arc::new(Schedule::new(config.limit, start));
+    let reqs = Arc::new(reqs);
+    let workers: Vec<_> = (0..config.concurrency.max(1))
+        .map(|_| {
+            let worker = worker(client.clone(), reqs.clone(), schedule.clone(), rate.clone());
+            tokio::spawn(worker)
+        })
+        .collect();
+
+    let mut recorder = Recorder::new();
+    for worker in workers {
+        let result = worker
+            .await
+            .map_err(|err| Error::Internal(format!("Benchmark worker failed: {err}")))?;
+        recorder.merge(&result);
+    }
+    Ok(recorder.report(start.elapsed()))
+}
+
+/// Sends requests until the schedule is done.
+async fn worker(
+    mut client: GrpcClient,
+    reqs: Arc<Vec<RequestMessage>>,
+    schedule: Arc<Schedule>,
+    rate: Option<Arc<Mutex<Interval>>>,
+) -> Recorder {
+    let mut recorder = Recorder::new();
+    while schedule.next() {
+        if let Some(rate) = &rate {
+            if !schedule.wait(rate).await {
+                break;
+            }
+        }
+        let start = Instant::now();
+        let result = call(&mut client, &reqs).await;
+        recorder.record(start.elapsed(), &result);
+    }
+    recorder
+}
+
+/// Makes a single call and waits until it completed.
+async fn call(client: &mut GrpcClient, reqs: &[RequestMessage]) -> Result<()> {
+    let method = reqs[0].method_descriptor();
+    match (method.is_client_streaming(), method.is_server_streaming()) {
+        (false, false) => client.unary(&reqs[0]).await.map(drop),
+        (false, true) => {
+            let mut stream = client.server_streaming(&reqs[0]).await?;
+            while stream.message().await?.is_some() {}
+            Ok(())
+        }
+        (true, false) => client.client_streaming(reqs.to_vec()).await.map(drop),
+        (true, true) => {
+            let (sender, response) = client.bidi_streaming(&reqs[0]).await?.into_parts();
+            for req in &reqs[1..] {
+                sender.send(req.clone())?;
+            }
+            // Closes the request stream.
+            drop(sender);
+            let mut stream = response.await?;
+            while stream.message().await?.is_some() {}
+            Ok(())
+        }
+    }
+}
+
+/// Decides whether the workers send another request.
+struct Schedule {
+    /// The number of requests that were not sent yet.
+    remaining: Option<AtomicU64>,
+    /// The time after which no more requests are sent.
+    deadline: Option<Instant>,
+}
+
+impl Schedule {
+    fn new(limit: BenchLimit, start: Instant) -> Self {
+        match limit {
+            BenchLimit::Requests(requests) => Self {
+                remaining: Some(AtomicU64::new(requests)),
+                deadline: None,
+            },
+            BenchLimit::Duration(duration) => Self {
+                remaining: None,
+                deadline: Some(start + duration),
+            },
+        }
+    }
+
+    /// Whether another request is sent. Takes the request from the
+    /// remaining requests.
+    fn next(&self) -> bool {
+        if let Some(remaining) = &self.remaining {
+            let taken =
+                remaining.fetch_update(Ordering::Relaxed, Ordering::Relaxed, |n| n.checked_sub(1));
+            return taken.is_ok();
+        }
+        self.deadline
+            .is_some_and(|deadline| Instant::now() < deadline)
+    }
+
+    /// Waits until the rate limit allows the next request. Returns
+    /// `false` if the deadline passed while waiting.
+    async fn wait(&self, rate: &Mutex<Interval>) -> bool {
+        let tick = async {
+            rate.lock().await.tick().await;
+        };
+        match self.deadline {
+            Some(deadline) => tokio::time::timeout_at(deadline, tick).await.is_ok(),
+            None => {
+                tick.await;
+                true
+            }
+        }
+    }
+}
+
+/// Collects the latencies and status codes of calls.
+struct Recorder {
+    /// The latencies in microseconds.
+    latencies: Histogram<u64>,
+    status: BTreeMap<String, u64>,
+    errors: BTreeMap<String, u64>,
+}
+
+impl Recorder {
+    fn new() -> Self {
+        Self {
+            // The bounds are valid, so creating the histogram cannot fail
+            latencies: Histogram::new_with_bounds(1, MAX_LATENCY, 3)
+                .expect("valid histogram bounds"),
+            status: BTreeMap::new(),
+            errors: BTreeMap::new(),
+        }
+    }
+
+    fn record(&mut self, latency: Duration, result: &Result<()>) {
+        let micros = u64::try_from(latency.as_micros()).unwrap_or(u64::MAX);
+        self.latencies.saturating_record(micros);
+        let code = match result {
+            Ok(()) => Code::Ok,
+            Err(err) => {
+                let message = err.to_string();
+                let message = message.lines().next().unwrap_or_default().to_string();
+                *self.errors.entry(message).or_default() += 1;
+                err.code()
+            }
+        };
+        *self.status.entry(format!("{code:?}")).or_default() += 1;
+    }
+
+    fn merge(&mut self, other: &Self) {
+        // Both histograms have the same bounds, so adding cannot fail
+        let _ = self.latencies.add(&other.latencies);
+        for (code, count) in &other.status {
+            *self.status.entry(code.clone()).or_default() += count;
+        }
+        for (message, count) in &other.errors {
+            *self.errors.entry(message.clone()).or_default() += count;
+        }
+    }
+
+    #[allow(clippy::cast_precision_loss)]
+    fn report(&self, total: Duration) -> BenchReport {
+        let latencies = &self.latencies;
+        let count = latencies.len();
+        let rps = if total.is_zero() {
+            0.0
+        } else {
+            count as f64 / total.as_secs_f64()
+        };
+        let (fastest, slowest) = if count == 0 {
+            (0, 0)
+        } else {
+            (latencies.min(), latencies.max())
+        };
+        let latency_distribution = if count == 0 {
+            Vec::new()
+        } else {
+            PERCENTILES
+                .iter()
+                .map(|&percentile| Percentile {
+                    percentile,
+                    latency: micros(latencies.value_at_percentile(percentile)),
+                })
+                .collect()
+        };
+        BenchReport {
+            count,
+            total,
+            rps,
+            fastest: micros(fastest),
+            slowest: micros(slowest),
+            average: mean(latencies),
+            histogram: buckets(latencies),
+            latency_distribution,
+            status_code_distribution: self.status.clone(),
+            error_distribution: self.errors.clone(),
+        }
+    }
+}
+
+fn micros(micros: u64) -> Duration {
+    Duration::from_micros(micros)
+}
+
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+fn mean(latencies: &Histogram<u64>) -> Duration {
+    Duration::from_nanos((latencies.mean() * 1000.0).round() as u64)
+}
+
+/// Splits the latencies into equally sized buckets between the fastest
+/// and the slowest call. All calls are in one bucket if they were
+/// equally fast.
+fn buckets(latencies: &Histogram<u64>) -> Vec<Bucket> {
+    if latencies.is_empty() {
+        return Vec::new();
+    }
+    let (min, max) = (latencies.min(), latencies.max());
+    let size = (max - min) / HISTOGRAM_BUCKETS + 1;
+    let n = ((max - min) / size + 1).min(HISTOGRAM_BUCKETS);
+    let mut buckets: Vec<Bucket> = (1..=n)
+        .map(|i| Bucket {
+            mark: micros((min + i * size).min(max)),
+            count: 0,
+        })
+        .collect();
+    for value in latencies.iter_recorded() {
+        let latency = latencies.highest_equivalent(value.value_iterated_to());
+        let index = (latency.saturating_sub(min) / size).min(n - 1);
+        // The index is less than the number of buckets
+        #[allow(clippy::cast_possible_truncation)]
+        let bucket = &mut buckets[index as usize];
+        bucket.count += value.count_at_value();
+    }
+    buckets
+}
+
+impl fmt::Display for BenchReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Summary:")?;
+        writeln!(f, "  Count:\t{}", self.count)?;
+        writeln!(f, "  Total:\t{}", format_millis(self.total))?;
+        writeln!(f, "  Slowest:\t{}", format_millis(self.slowest))?;
+        writeln!(f, "  Fastest:\t{}", format_millis(self.fastest))?;
+        writeln!(f, "  Average:\t{}", format_millis(self.average))?;
+        writeln!(f, "  Requests/sec:\t{:.2}", self.rps)?;
+
+        writeln!(f, "\nResponse time histogram:")?;
+        let max = self.histogram.iter().map(|b| b.count).max().unwrap_or(0);
+        for bucket in &self.histogram {
+            let width = (bucket.count * BAR_WIDTH).checked_div(max).unwrap_or(0);
+            let bar = "∎".repeat(usize::try_from(width).unwrap_or_default());
+            let mark = bucket.mark.as_secs_f64() * 1000.0;
+            writeln!(f, "  {mark:.3} [{}]\t|{bar}", bucket.count)?;
+        }
+
+        writeln!(f, "\nLatency distribution:")?;
+        for percentile in &self.latency_distribution {
+            let latency = format_millis(percentile.latency);
+            writeln!(f, "  {} % in {latency}", percentile.percentile)?;
+        }
+
+        writeln!(f, "\nStatus code distribution:")?;
+        for (code, count) in &self.status_code_distribution {
+            writeln!(f, "  [{code}]\t{count} responses")?;
+        }
+
+        if !self.error_distribution.is_empty() {
+            writeln!(f, "\nError distribution:")?;
+            for (message, count) in &self.error_distribution {
+                writeln!(f, "  [{count}]\t{message}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+fn format_millis(duration: Duration) -> String {
+    format!("{:.2} ms", duration.as_secs_f64() * 1000.0)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::client::health::test::serve_health;
+    use crate::client::health::{health_request, HealthMethod};
+    use crate::client::test::{load_echo_request, serve_echo_streams, with_text};
+    use crate::client::transport::Transport;
+
+    #[tokio::test]
+    async fn test_bench_requests() {
+        // given
+        let (address, _reporter) = serve_health().await;
+        let client = GrpcClient::from_address(&address, None, Transport::Grpc).unwrap();
+        let req = health_request(HealthMethod::Check, "test").unwrap();
+        let config = BenchConfig {
+            limit: BenchLimit::Requests(20),
+            concurrency: 4,
+            rate: None,
+        };
+
+        // when
+        let report = bench(client, vec![req], config).await.unwrap();
+
+        // then
+        assert_eq!(report.count, 20);
+        assert_eq!(report.histogram.iter().map(|b| b.count).sum::<u64>(), 20);
+        assert_eq!(report.latency_distribution.len(), PERCENTILES.len());
+        assert!(report.fastest <= report.average && report.average <= report.slowest);
+        assert_eq!(
+            report.status_code_distribution,
+            BTreeMap::from([(String::from("Ok"), 20)])
+        );
+        assert!(report.error_distribution.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_bench_duration_with_rate() {
+        // given
+        let (address, _reporter) = serve_health().await;
+        let client = GrpcClient::from_address(&address, None, Transport::Grpc).unwrap();
+        let req = health_request(HealthMethod::Check, "unknown").unwrap();
+        let config = BenchConfig {
+            limit: BenchLimit::Duration(Duration::from_millis(200)),
+            concurrency: 2,
+            rate: Some(50.0),
+        };
+
+        // when
+        let report = bench(client, vec![req], config).await.unwrap();
+
+        // then
+        assert!(report.count > 0 && report.count <= 12, "{}", report.count);
+        assert_eq!(
+            report.status_code_distribution,
+            BTreeMap::from([(String::from("NotFound"), report.count)])
+        );
+        assert_eq!(
+            report.error_distribution.values().sum::<u64>(),
+            report.count
+        );
+    }
+
+    #[tokio::test]
+    async fn test_bench_bidi_streaming() {
+        // given
+        let (addr, _) = serve_echo_streams().await;
+        let client =
+            GrpcClient::from_address(&format!("http://{addr}"), None, Transport::Grpc).unwrap();
+        let messages = |method| {
+            let req = load_echo_request(method, "0");
+            let mut reqs = vec![req.clone()];
+            reqs.extend((1..20).map(|i| with_text(&req, &i.to_string())));
+            reqs
+        };
+        let config = BenchConfig {
+            limit: BenchLimit::Requests(4),
+            concurrency: 2,
+            rate: None,
+        };
+
+        // when
+        let streamed = bench(client.clone(), messages("BidiStream"), config).await;
+        let collected = bench(client, messages("BidiCollect"), config).await;
+
+        // then
+        for report in [streamed.unwrap(), collected.unwrap()] {
+            assert_eq!(report.count, 4);
+            assert_eq!(
+                report.status_code_distribution,
+                BTreeMap::from([(String::from("Ok"), 4)])
+            );
+        }
+    }
+
+    #[test]
+    fn test_bench_report_buckets() {
+        // given
+        let mut recorder = Recorder::new();
+        for millis in [1, 3, 3, 11] {
+            recorder.record(Duration::from_millis(millis), &Ok(()));
+        }
+
+        // when
+        let report = recorder.report(Duration::from_secs(1));
+
+        // then
+        assert_eq!(report.count, 4);
+        assert_eq!(report.rps, 4.0);
+        assert_eq!(report.fastest, Duration::from_millis(1));
+        assert_eq!(report.histogram.len(), 10);
+        assert_eq!(report.histogram[0].count, 1);
+        assert_eq!(report.histogram[1].count, 2);
+        assert_eq!(report.histogram[9].count, 1);
+        assert!(report.to_string().contains("[Ok]\t4 responses"));
+    }
+}
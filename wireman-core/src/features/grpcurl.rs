@@ -1,6 +1,11 @@
 use std::collections::HashMap;
 use std::fmt::Write;
 
+use crate::client::tls::TlsConfig;
+use crate::client::unix_socket_path;
+use crate::error::Error;
+use crate::Result;
+use http::uri::Scheme;
 use http::Uri;
 use prost_reflect::MethodDescriptor;
 
@@ -13,18 +18,26 @@ use prost_reflect::MethodDescriptor;
 /// # Parameters
 ///
 /// - `includes`: A list of include directories used to locate .proto files.
-/// - `uri`: The address URI of the `gRPC` server (e.g., "localhost:50051").
+/// - `address`: The address of the `gRPC` server (e.g., "http://localhost:50051"
+///   or "unix:///path/to/socket").
 /// - `message`: The request data in JSON format.
 /// - `method_desc`: The method descriptor for the `gRPC` method.
 /// - `metadata`: Key-value metadata headers to be included in the request.
+/// - `tls`: The TLS settings, `https` addresses skip the verification of the
+///   server certificate if insecure. The authority override applies to all
+///   addresses.
+///
+/// # Errors
+/// - Failed to parse the address
 #[allow(clippy::implicit_hasher)]
-pub fn grpcurl<T: Into<Uri>>(
+pub fn grpcurl(
     includes: &[String],
-    uri: T,
+    address: &str,
     message: &str,
     method_desc: &MethodDescriptor,
     metadata: &HashMap<String, String>,
-) -> String {
+    tls: Option<&TlsConfig>,
+) -> Result<String> {
     // The includes
     let imports = includes.iter().fold(String::new(), |mut result, include| {
         let _ = write!(result, "-import-path {include} ");
@@ -35,10 +48,28 @@ pub fn grpcurl<T: Into<Uri>>(
     let file_desc = method_desc.parent_file();
     let proto = file_desc.file_descriptor_proto().name();
 
-    // The host
-    let uri = uri.into();
-    let host = uri.host().unwrap_or("");
-    let port = uri.port_u16().unwrap_or(80);
+    // The host or the unix socket
+    let mut https = false;
+    let target = if let Some(path) = unix_socket_path(address) {
+        format!("-unix {path}")
+    } else {
+        let uri = Uri::try_from(address)
+            .map_err(|_| Error::Internal(String::from("Failed to parse address")))?;
+        https = uri.scheme() == Some(&Scheme::HTTPS);
+        let host = uri.host().unwrap_or("");
+        let port = uri.port_u16().unwrap_or(if https { 443 } else { 80 });
+        format!("{host}:{port}")
+    };
+
+    // The TLS settings
+    let mut security = match (https, tls.is_some_and(TlsConfig::insecure)) {
+        (false, _) => String::from(" -plaintext"),
+        (true, true) => String::from(" -insecure"),
+        (true, false) => String::new(),
+    };
+    if let Some(authority) = tls.and_then(TlsConfig::authority) {
+        let _ = write!(security, " -authority {authority}");
+    }
 
     // The method name
     let method = method_desc.full_name();
@@ -51,9 +82,9 @@ pub fn grpcurl<T: Into<Uri>>(
             result
         });
 
-    format!(
-        "grpcurl -d @ {imports}-proto {proto}{metadata} -plaintext {host}:{port} {method} <<EOM\n{message}\nEOM"
-    )
+    Ok(format!(
+        "grpcurl -d @ {imports}-proto {proto}{metadata}{security} {target} {method} <<EOM\n{message}\nEOM"
+    ))
 }
 
 #[cfg(test)]
@@ -67,7 +98,7 @@ mod test {
     fn test_request_as_grpcurl() {
         // given
         let includes = vec!["/Users/myworkspace".to_string()];
-        let given_uri = Uri::from_static("http://localhost:50051");
+        let given_address = "http://localhost:50051";
         let test_message = load_test_message("Simple");
         let given_method = test_message.method_descriptor();
         let given_message = "{\n  \"number\": 0\n}";
@@ -76,11 +107,62 @@ mod test {
         // when
         let cmd = grpcurl(
             &includes,
-            given_uri,
+            given_address,
+            given_message,
+            &given_method,
+            &HashMap::new(),
+            None,
+        )
+        .unwrap();
+
+        // then
+        assert_eq!(cmd, expected);
+    }
+
+    #[test]
+    fn test_request_as_grpcurl_unix() {
+        // given
+        let given_address = "unix:///tmp/grpc.sock";
+        let test_message = load_test_message("Simple");
+        let given_method = test_message.method_descriptor();
+        let given_message = "{}";
+        let expected = "grpcurl -d @ -proto test_files/test.proto -plaintext -unix /tmp/grpc.sock proto.TestService.Simple <<EOM\n{}\nEOM";
+
+        // when
+        let cmd = grpcurl(
+            &[],
+            given_address,
             given_message,
             &given_method,
             &HashMap::new(),
-        );
+            None,
+        )
+        .unwrap();
+
+        // then
+        assert_eq!(cmd, expected);
+    }
+
+    #[test]
+    fn test_request_as_grpcurl_tls() {
+        // given
+        let test_message = load_test_message("Simple");
+        let given_method = test_message.method_descriptor();
+        let tls = TlsConfig::default()
+            .with_insecure(true)
+            .with_authority(Some(String::from("example.com")));
+        let expected = "grpcurl -d @ -proto test_files/test.proto -insecure -authority example.com 127.0.0.1:443 proto.TestService.Simple <<EOM\n{}\nEOM";
+
+        // when
+        let cmd = grpcurl(
+            &[],
+            "https://127.0.0.1",
+            "{}",
+            &given_method,
+            &HashMap::new(),
+            Some(&tls),
+        )
+        .unwrap();
 
         // then
         assert_eq!(cmd, expected);
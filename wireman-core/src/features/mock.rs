@@ -0,0 +1,592 @@
+//! A mock server which serves all services of a [`ProtoDescriptor`].
+//! The requests are decoded and the responses encoded by the reverse
+//! of the client codec. A response is taken from the first matching
+//! [`MockRule`], from the saved responses of the method, or from the
+//! template of the response message, in this order.
+use crate::client::codec::ServerCodec;
+use crate::client::service_config::deserialize_code;
+use crate::descriptor::{DynamicMessage, RequestMessage, ResponseMessage};
+use crate::error::{Code, Error};
+use crate::{ProtoDescriptor, Result};
+use hyper::server::conn::AddrIncoming;
+use hyper::service::make_service_fn;
+use hyper::Body;
+use prost_reflect::{MethodDescriptor, SerializeOptions};
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::convert::Infallible;
+use std::future::Future;
+use std::path::Path;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use std::time::Duration;
+use tokio::net::TcpListener;
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::ReceiverStream;
+use tokio_stream::StreamExt;
+use tonic::body::BoxBody;
+use tonic::codec::CompressionEncoding;
+use tonic::server::Grpc;
+use tonic::{Request, Response, Status, Streaming};
+use tower::{service_fn, Service};
+
+/// The number of response messages of a bidirectional stream that
+/// can be queued.
+const STREAM_BUFFER_SIZE: usize = 16;
+
+/// A rule which answers the requests of a method. Rules are read from
+/// json files holding a list of rules.
+#[derive(Debug, Clone, Deserialize)]
+pub struct MockRule {
+    /// The full name of the method, e.g. `helloworld.Greeter/SayHello`
+    /// or `helloworld.Greeter.SayHello`.
+    pub method: String,
+    /// The fields the request must have, as json. Nested messages are
+    /// matched by their fields, all other values must be equal. The
+    /// fields are named as in the proto file, e.g. `user_id`, and match
+    /// default values as well. The rule matches all requests of the
+    /// method if `None`.
+    #[serde(default, rename = "match")]
+    pub matches: Option<Value>,
+    /// The response message as json. Server streams send each message
+    /// of a json array. The template of the response message is sent
+    /// if `None`.
+    #[serde(default)]
+    pub response: Option<Value>,
+    /// The error status which is sent instead of a response.
+    #[serde(default)]
+    pub status: Option<MockStatus>,
+    /// The time to wait before responding in milliseconds.
+    #[serde(default)]
+    pub delay_ms: Option<u64>,
+}
+
+/// The error status of a [`MockRule`].
+#[derive(Debug, Clone, Deserialize)]
+pub struct MockStatus {
+    /// The status code, by its name, e.g. `NOT_FOUND`, or its number.
+    #[serde(deserialize_with = "deserialize_code")]
+    pub code: Code,
+    /// The error message.
+    #[serde(default)]
+    pub message: String,
+}
+
+impl MockRule {
+    /// Reads a list of rules from a json file.
+    ///
+    /// # Errors
+    /// - Failed to read the file
+    /// - The file is not a list of rules
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Vec<Self>> {
+        let json = std::fs::read_to_string(path).map_err(Error::ReadMockRulesError)?;
+        serde_json::from_str(&json).map_err(|err| Error::MockRuleError(err.to_string()))
+    }
+
+    /// Whether the rule answers the requests of the method.
+    fn is_method(&self, method: &MethodDescriptor) -> bool {
+        let name = self.method.trim_start_matches('/');
+        name == method.full_name()
+            || name.split_once('/').is_some_and(|(service, name)| {
+                service == method.parent_service().full_name() && name == method.name()
+            })
+    }
+
+    /// Whether the rule answers the request, given as json.
+    fn matches(&self, request: &Value) -> bool {
+        match &self.matches {
+            Some(fields) => is_subset(fields, request),
+            None => true,
+        }
+    }
+
+    /// Returns the response messages of the rule. Fails if a response
+    /// is not a valid response message.
+    fn responses(&self, method: &MethodDescriptor) -> Result<Vec<DynamicMessage>> {
+        let values = match &self.response {
+            None => return Ok(vec![template(method)]),
+            Some(Value::Array(values)) if method.is_server_streaming() => values.clone(),
+            Some(value) => vec![value.clone()],
+        };
+        values
+            .iter()
+            .map(|value| from_json(method, &value.to_string()))
+            .collect()
+    }
+}
+
+/// Whether all fields of the pattern are in the value. Objects are
+/// compared field by field, all other values must be equal.
+fn is_subset(pattern: &Value, value: &Value) -> bool {
+    match (pattern, value) {
+        (Value::Object(pattern), Value::Object(value)) => pattern
+            .iter()
+            .all(|(key, field)| value.get(key).is_some_and(|value| is_subset(field, value))),
+        (pattern, value) => pattern == value,
+    }
+}
+
+/// Returns the request as json to which the rules are matched. The
+/// fields are named as in the proto file and default values are included.
+fn request_json(req: &RequestMessage) -> std::result::Result<Value, serde_json::Error> {
+    let options = SerializeOptions::new()
+        .stringify_64_bit_integers(false)
+        .skip_default_fields(false)
+        .use_proto_field_name(true);
+    req.message()
+        .serialize_with_options(serde_json::value::Serializer, &options)
+}
+
+/// Returns the response message of a method from json.
+fn from_json(method: &MethodDescriptor, json: &str) -> Result<DynamicMessage> {
+    let mut message = DynamicMessage::new(method.output());
+    message.from_json(json).map_err(|err| {
+        Error::MockRuleError(format!("invalid response of {}: {err}", method.full_name()))
+    })?;
+    Ok(message)
+}
+
+/// Returns the template of the response message of a method.
+fn template(method: &MethodDescriptor) -> DynamicMessage {
+    let mut message = DynamicMessage::new(method.output());
+    message.apply_template();
+    message
+}
+
+/// Serves the methods of a [`ProtoDescriptor`] with mock responses.
+#[derive(Debug, Clone)]
+pub struct Mock {
+    desc: ProtoDescriptor,
+    rules: Vec<MockRule>,
+    /// The saved response messages by the full method name.
+    saved: HashMap<String, Vec<DynamicMessage>>,
+}
+
+impl Mock {
+    /// Returns a mock which answers all requests with the template of
+    /// the response message.
+    #[must_use]
+    pub fn new(desc: ProtoDescriptor) -> Self {
+        Self {
+            desc,
+            rules: Vec::new(),
+            saved: HashMap::new(),
+        }
+    }
+
+    /// Adds rules, which take precedence over the saved responses.
+    /// The first matching rule answers a request.
+    ///
+    /// # Errors
+    /// - The method of a rule is unknown
+    /// - The response of a rule is not a valid response message
+    pub fn with_rules(mut self, rules: Vec<MockRule>) -> Result<Self> {
+        for rule in &rules {
+            let method = self
+                .find_method(&rule.method)
+                .ok_or_else(|| Error::MockRuleError(format!("unknown method {}", rule.method)))?;
+            rule.responses(&method)?;
+        }
+        self.rules.extend(rules);
+        Ok(self)
+    }
+
+    /// Sets the saved response messages of a method, as json. Server
+    /// streams send all messages, other methods the first one. Messages
+    /// which are not valid response messages are skipped, their errors
+    /// are returned with the mock.
+    #[must_use]
+    pub fn with_saved_responses(
+        mut self,
+        method: &MethodDescriptor,
+        messages: &[String],
+    ) -> (Self, Vec<Error>) {
+        let mut errors = Vec::new();
+        let messages: Vec<_> = messages
+            .iter()
+            .filter_map(|json| from_json(method, json).map_err(|err| errors.push(err)).ok())
+            .collect();
+        if !messages.is_empty() {
+            self.saved.insert(method.full_name().to_string(), messages);
+        }
+        (self, errors)
+    }
+
+    /// Returns the response messages of a request. Unary and client
+    /// streaming methods respond with the first message.
+    ///
+    /// # Errors
+    /// - The matching rule has an error status
+    pub async fn respond(
+        &self,
+        req: &RequestMessage,
+    ) -> std::result::Result<Vec<ResponseMessage>, Status> {
+        let method = req.method_descriptor();
+        let request = request_json(req).map_err(|err| Status::invalid_argument(err.to_string()))?;
+        let rule = self
+            .rules
+            .iter()
+            .find(|rule| rule.is_method(&method) && rule.matches(&request));
+        let messages = match rule {
+            Some(rule) => {
+                if let Some(delay) = rule.delay_ms {
+                    tokio::time::sleep(Duration::from_millis(delay)).await;
+                }
+                if let Some(status) = &rule.status {
+                    return Err(Status::new(status.code, status.message.clone()));
+                }
+                rule.responses(&method)
+                    .map_err(|err| Status::internal(err.to_string()))?
+            }
+            None => self
+                .saved
+                .get(method.full_name())
+                .cloned()
+                .unwrap_or_else(|| vec![template(&method)]),
+        };
+        let count = if method.is_server_streaming() {
+            messages.len()
+        } else {
+            1
+        };
+        Ok(messages
+            .into_iter()
+            .take(count)
+            .map(|message| {
+                let mut response = ResponseMessage::new(method.output(), method.clone());
+                response.set_message(message);
+                response
+            })
+            .collect())
+    }
+
+    /// Serves the mock on the listener. Native `gRPC` and `gRPC-Web`
+    /// requests are accepted, the latter from any origin.
+    ///
+    /// # Errors
+    /// - Failed to accept connections on the listener
+    pub async fn serve(self, listener: TcpListener) -> Result<()> {
+        let service = tonic_web::enable(MockService(Arc::new(self)));
+        let make_service = make_service_fn(move |_| {
+            let service = service.clone();
+            async move { Ok::<_, Infallible>(service) }
+        });
+        let incoming = AddrIncoming::from_listener(listener)
+            .map_err(|err| Error::MockServerError(err.to_string()))?;
+        hyper::Server::builder(incoming)
+            .serve(make_service)
+            .await
+            .map_err(|err| Error::MockServerError(err.to_string()))
+    }
+
+    /// Returns a method by its full name, e.g. `helloworld.Greeter/SayHello`
+    /// or `helloworld.Greeter.SayHello`.
+    fn find_method(&self, name: &str) -> Option<MethodDescriptor> {
+        let name = name.trim_start_matches('/');
+        let (service, method) = name.rsplit_once('/').or_else(|| name.rsplit_once('.'))?;
+        self.desc.get_method_by_name(service, method)
+    }
+
+    /// Answers a `gRPC` call.
+    async fn handle(self: Arc<Self>, req: http::Request<Body>) -> http::Response<BoxBody> {
+        let path = req.uri().path();
+        let Some(method) = self.find_method(path).filter(|_| path.starts_with('/')) else {
+            return Status::unimplemented(format!("unknown method {path}")).to_http();
+        };
+        let mut grpc = Grpc::new(ServerCodec::new(method.clone()))
+            .accept_compressed(CompressionEncoding::Gzip)
+            .send_compressed(CompressionEncoding::Gzip);
+        match (method.is_client_streaming(), method.is_server_streaming()) {
+            (false, false) => {
+                let service = service_fn(move |req: Request<RequestMessage>| {
+                    let mock = self.clone();
+                    async move {
+                        let response = mock.respond(req.get_ref()).await?;
+                        response
+                            .into_iter()
+                            .next()
+                            .map(Response::new)
+                            .ok_or_else(|| Status::internal("no response message"))
+                    }
+                });
+                grpc.unary(service, req).await
+            }
+            (false, true) => {
+                let service = service_fn(move |req: Request<RequestMessage>| {
+                    let mock = self.clone();
+                    async move {
+                        let response = mock.respond(req.get_ref()).await?;
+                        Ok(Response::new(tokio_stream::iter(
+                            response.into_iter().map(Ok),
+                        )))
+                    }
+                });
+                grpc.server_streaming(service, req).await
+            }
+            (true, false) => {
+                let method = method.clone();
+                let service = service_fn(move |req: Request<Streaming<RequestMessage>>| {
+                    let mock = self.clone();
+                    let method = method.clone();
+                    async move {
+                        // Client streams are answered by their first message
+                        let mut stream = req.into_inner();
+                        let mut first_req = None;
+                        while let Some(req) = stream.next().await {
+                            let req = req?;
+                            first_req.get_or_insert(req);
+                        }
+                        let req = first_req
+                            .unwrap_or_else(|| RequestMessage::new(method.input(), method.clone()));
+                        let response = mock.respond(&req).await?;
+                        response
+                            .into_iter()
+                            .next()
+                            .map(Response::new)
+                            .ok_or_else(|| Status::internal("no response message"))
+                    }
+                });
+                grpc.client_streaming(service, req).await
+            }
+            (true, true) => {
+                let service = service_fn(move |req: Request<Streaming<RequestMessage>>| {
+                    let mock = self.clone();
+                    async move {
+                        // Every message of a bidirectional stream is answered
+                        let (sx, rx) = mpsc::channel(STREAM_BUFFER_SIZE);
+                        tokio::spawn(answer_stream(mock, req.into_inner(), sx));
+                        Ok::<_, Status>(Response::new(ReceiverStream::new(rx)))
+                    }
+                });
+                grpc.streaming(service, req).await
+            }
+        }
+    }
+}
+
+/// Answers every request of a bidirectional stream until the client
+/// closes the stream or a rule sends an error status.
+async fn answer_stream(
+    mock: Arc<Mock>,
+    mut stream: Streaming<RequestMessage>,
+    sx: mpsc::Sender<std::result::Result<ResponseMessage, Status>>,
+) {
+    while let Some(req) = stream.next().await {
+        let response = match req {
+            Ok(req) => mock.respond(&req).await,
+            Err(status) => Err(status),
+        };
+        match response {
+            Ok(messages) => {
+                for message in messages {
+                    if sx.send(Ok(message)).await.is_err() {
+                        return;
+                    }
+                }
+            }
+            Err(status) => {
+                let _ = sx.send(Err(status)).await;
+                return;
+            }
+        }
+    }
+}
+
+/// The service which answers all calls of a [`Mock`].
+#[derive(Debug, Clone)]
+struct MockService(Arc<Mock>);
+
+impl Service<http::Request<Body>> for MockService {
+    type Response = http::Response<BoxBody>;
+    type Error = Infallible;
+    type Future =
+        Pin<Box<dyn Future<Output = std::result::Result<Self::Response, Infallible>> + Send>>;
+
+    fn poll_ready(&mut self, _: &mut Context<'_>) -> Poll<std::result::Result<(), Infallible>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, req: http::Request<Body>) -> Self::Future {
+        let mock = self.0.clone();
+        Box::pin(async move { Ok(mock.handle(req).await) })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::client::transport::Transport;
+    use crate::client::GrpcClient;
+
+    fn load_mock() -> Mock {
+        let desc = ProtoDescriptor::new(["test_files"], ["mock.proto"]).unwrap();
+        Mock::new(desc)
+    }
+
+    fn load_request(mock: &Mock, method: &str, json: &str) -> RequestMessage {
+        let method = mock.find_method(method).unwrap();
+        let mut req = RequestMessage::new(method.input(), method);
+        req.message_mut().from_json(json).unwrap();
+        req
+    }
+
+    fn response_json(response: &ResponseMessage) -> String {
+        response.message.to_json().unwrap()
+    }
+
+    fn rules(json: &str) -> Vec<MockRule> {
+        serde_json::from_str(json).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_respond() {
+        // given
+        let mock = load_mock();
+        let method = mock.find_method("mock.Greeter/SayHello").unwrap();
+        let (mock, errors) = mock
+            .with_rules(rules(
+                r#"[
+                    {
+                        "method": "mock.Greeter/SayHello",
+                        "match": {"name": "bob"},
+                        "response": {"message": "Hello bob", "count": 1}
+                    },
+                    {
+                        "method": "mock.Greeter.SayHello",
+                        "match": {"name": "eve"},
+                        "status": {"code": "NOT_FOUND", "message": "no eve"}
+                    }
+                ]"#,
+            ))
+            .unwrap()
+            .with_saved_responses(
+                &method,
+                &[String::from(r#"{"message": "saved"}"#), String::new()],
+            );
+        let bob = load_request(&mock, "mock.Greeter/SayHello", r#"{"name": "bob"}"#);
+        let eve = load_request(&mock, "mock.Greeter/SayHello", r#"{"name": "eve"}"#);
+        let other = load_request(&mock, "mock.Greeter/SayHello", r#"{"name": "tom"}"#);
+
+        // when
+        let bob = mock.respond(&bob).await.unwrap();
+        let eve = mock.respond(&eve).await.unwrap_err();
+        let other = mock.respond(&other).await.unwrap();
+
+        // then
+        assert_eq!(
+            response_json(&bob[0]),
+            r#"{"message":"Hello bob","count":1}"#
+        );
+        assert_eq!(eve.code(), Code::NotFound);
+        assert_eq!(eve.message(), "no eve");
+        assert_eq!(response_json(&other[0]), r#"{"message":"saved","count":0}"#);
+        assert_eq!(errors.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_respond_match_default_field() {
+        // given
+        let mock = load_mock()
+            .with_rules(rules(
+                r#"[
+                    {
+                        "method": "mock.Greeter/SayHello",
+                        "match": {"repeat_count": 0},
+                        "response": {"message": "Hello once"}
+                    }
+                ]"#,
+            ))
+            .unwrap();
+        let once = load_request(&mock, "mock.Greeter/SayHello", r#"{"name": "bob"}"#);
+        let twice = load_request(
+            &mock,
+            "mock.Greeter/SayHello",
+            r#"{"name": "bob", "repeatCount": 2}"#,
+        );
+
+        // when
+        let once = mock.respond(&once).await.unwrap();
+        let twice = mock.respond(&twice).await.unwrap();
+
+        // then
+        assert_eq!(
+            response_json(&once[0]),
+            r#"{"message":"Hello once","count":0}"#
+        );
+        assert!(!response_json(&twice[0]).contains("Hello once"));
+    }
+
+    #[test]
+    fn test_with_rules_unknown_method() {
+        // given
+        let mock = load_mock();
+
+        // when
+        let result = mock.with_rules(rules(r#"[{"method": "mock.Greeter/Unknown"}]"#));
+
+        // then
+        assert!(matches!(result, Err(Error::MockRuleError(_))));
+    }
+
+    #[test]
+    fn test_is_subset() {
+        // given
+        let value = serde_json::json!({"name": "bob", "items": [1, 2], "nested": {"a": 1, "b": 2}});
+
+        // when
+        let matches = [
+            serde_json::json!({"name": "bob"}),
+            serde_json::json!({"nested": {"b": 2}}),
+            serde_json::json!({"items": [1]}),
+            serde_json::json!({"missing": null}),
+        ]
+        .map(|pattern| is_subset(&pattern, &value));
+
+        // then
+        assert_eq!(matches, [true, true, false, false]);
+    }
+
+    #[tokio::test]
+    async fn test_serve() {
+        // given
+        let mock = load_mock()
+            .with_rules(rules(
+                r#"[{
+                    "method": "mock.Greeter/StreamHello",
+                    "response": [{"message": "one"}, {"message": "two"}]
+                }]"#,
+            ))
+            .unwrap();
+        let unary = load_request(&mock, "mock.Greeter/SayHello", r#"{"name": "bob"}"#);
+        let stream = load_request(&mock, "mock.Greeter/StreamHello", r#"{"name": "bob"}"#);
+        let method = mock.find_method("mock.Greeter/SayHello").unwrap();
+        let expected = template(&method).to_json().unwrap();
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(mock.serve(listener));
+
+        // when
+        let mut client = GrpcClient::from_address(&address, None, Transport::Grpc).unwrap();
+        let mut web_client = GrpcClient::from_address(&address, None, Transport::GrpcWeb).unwrap();
+        let web = web_client.unary(&unary).await.unwrap();
+        let unary = client.unary(&unary).await.unwrap();
+        let mut stream = client.server_streaming(&stream).await.unwrap();
+        let mut messages = Vec::new();
+        while let Some(message) = stream.message().await.unwrap() {
+            messages.push(response_json(&message));
+        }
+
+        // then
+        assert_eq!(response_json(&unary), expected);
+        assert_eq!(response_json(&web), expected);
+        assert_eq!(
+            messages,
+            vec![
+                r#"{"message":"one","count":0}"#,
+                r#"{"message":"two","count":0}"#
+            ]
+        );
+    }
+}
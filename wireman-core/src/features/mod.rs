@@ -1,2 +1,6 @@
+pub mod bench;
 pub mod grpcurl;
+pub mod mock;
+pub use bench::bench;
 pub use grpcurl::grpcurl;
+pub use mock::Mock;
@@ -19,18 +19,18 @@ fn _test_async() -> Result<()> {
     )?;
     // Services and methods
     let service = &desc.get_services()[0];
-    let method = &desc.get_methods(&service)[1];
+    let method = &desc.get_methods(service)[1];
 
     // Request
-    let mut req = desc.get_request(&method);
+    let mut req = desc.get_request(method);
     req.message_mut().apply_template();
     req.set_address("http://localhost:50051");
     req.insert_metadata("key", "value")?;
 
     // Call grpc
     let rt = create_runtime()?;
-    let _ = rt.spawn(async move {
-        let resp = call_unary_async(&req).await;
+    let _handle = rt.spawn(async move {
+        let resp = call_unary_async(&req, None).await;
         println!("Response {:?}", resp);
         if let Err(err) = tx.send(String::from("Hello!")) {
             eprintln!("Error sending event: {:?}", err);
@@ -48,7 +48,7 @@ fn _test_async() -> Result<()> {
     // Shut down runtime
     rt.shutdown_background();
 
-    return Ok(());
+    Ok(())
 }
 
 fn _test_oneof() -> Result<()> {
@@ -61,10 +61,10 @@ fn _test_oneof() -> Result<()> {
         println!("{:?}", service.name());
     }
     let service = &services[0];
-    let method = &desc.get_methods(&service)[0];
-    let mut req = desc.get_request(&method);
+    let method = &desc.get_methods(service)[0];
+    let mut req = desc.get_request(method);
 
     req.message_mut().apply_template();
     println!("{:?}", req.message().to_json());
-    return Ok(());
+    Ok(())
 }
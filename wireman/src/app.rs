@@ -1,17 +1,27 @@
 use crate::{
     context::{AppContext, Tab},
     input::{HeadersInput, MessagesInput, SelectionInput},
-    model::messages::{do_request, RequestResult},
+    model::{
+        headers::{health::do_health_check, HealthStatus},
+        messages::{
+            do_bidi_streaming_request, do_client_streaming_request, do_request,
+            do_server_streaming_request, RequestResult, SessionEvent, StreamResult,
+        },
+        MessagesModel,
+    },
     term::Term,
     view::root::Root,
+    widgets::editor::ErrorKind,
 };
 use config::Config;
+use core::MethodDescriptor;
 use crossterm::event::{Event, EventStream, KeyCode, KeyEvent, KeyModifiers};
 use futures::StreamExt;
 use std::error::Error;
 use tokio::{
     select,
     sync::mpsc::{self, Receiver, Sender},
+    task::JoinHandle,
 };
 
 type Result<T> = std::result::Result<T, Box<dyn Error>>;
@@ -35,7 +45,42 @@ pub struct App {
     internal_stream: InternalStream,
 }
 
-type InternalStreamData = RequestResult;
+/// The events that are sent over the internal stream.
+pub enum InternalStreamData {
+    /// The result of a unary grpc request
+    Request(RequestResult),
+    /// A message or the final status of a streaming grpc request
+    Stream(StreamResult),
+    /// An event of a bidirectional streaming session
+    Session(SessionEvent),
+    /// The result of a health check
+    Health(HealthStatus),
+}
+
+impl From<RequestResult> for InternalStreamData {
+    fn from(value: RequestResult) -> Self {
+        Self::Request(value)
+    }
+}
+
+impl From<StreamResult> for InternalStreamData {
+    fn from(value: StreamResult) -> Self {
+        Self::Stream(value)
+    }
+}
+
+impl From<SessionEvent> for InternalStreamData {
+    fn from(value: SessionEvent) -> Self {
+        Self::Session(value)
+    }
+}
+
+impl From<HealthStatus> for InternalStreamData {
+    fn from(value: HealthStatus) -> Self {
+        Self::Health(value)
+    }
+}
+
 struct InternalStream {
     sx: Sender<InternalStreamData>,
     rx: Receiver<InternalStreamData>,
@@ -43,17 +88,18 @@ struct InternalStream {
 
 impl InternalStream {
     fn new() -> Self {
-        let (sx, rx) = mpsc::channel::<RequestResult>(10);
+        let (sx, rx) = mpsc::channel::<InternalStreamData>(10);
         Self { sx, rx }
     }
 }
 
 impl App {
     #[allow(clippy::needless_pass_by_value)]
-    pub fn new(env: Config) -> Result<App> {
+    pub async fn new(env: Config) -> Result<App> {
+        let ctx = AppContext::new(&env).await?;
         Ok(App {
             term: Term::new()?,
-            ctx: AppContext::new(&env)?,
+            ctx,
             should_quit: false,
             crossterm_stream: EventStream::new(),
             internal_stream: InternalStream::new(),
@@ -62,7 +108,7 @@ impl App {
 
     #[allow(clippy::needless_pass_by_value)]
     pub async fn run(env: Config) -> Result<()> {
-        let mut app = Self::new(env)?;
+        let mut app = Self::new(env).await?;
         while !app.should_quit {
             app.draw()?;
             app.handle_events().await?;
@@ -126,17 +172,23 @@ impl App {
             },
         }
 
+        // Dispatch the health check in a seperate thread.
+        if self.ctx.headers.borrow().health.dispatch {
+            self.ctx.headers.borrow_mut().health.dispatch = false;
+            self.dispatch_health_check(sx.clone());
+        }
+
         // Dispatch the grpc request in a seperate thread.
         if self.ctx.messages.borrow().dispatch {
             let mut messages_model = self.ctx.messages.borrow_mut();
             messages_model.dispatch = false;
-            match messages_model.collect_request() {
-                Ok(req) => {
-                    let handler = tokio::spawn(async move {
-                        let resp = do_request(req).await;
-                        let _ = sx.send(resp).await;
-                    });
+            match Self::dispatch_request(&mut messages_model, sx) {
+                Ok(handler) => {
                     messages_model.handler = Some(handler);
+                    messages_model.history_spot = messages_model
+                        .selected_method
+                        .clone()
+                        .map(|method| (messages_model.history_model.save_spot(), method));
                 }
                 Err(err) => {
                     messages_model.response.set_text(&err.string());
@@ -146,8 +198,102 @@ impl App {
         }
     }
 
-    fn handle_internal_event(&mut self, result: &RequestResult) {
-        result.set(&mut self.ctx.messages.borrow_mut().response.editor);
-        self.ctx.messages.borrow_mut().handler.take();
+    /// Collects the request(s) and spawns a task which executes the
+    /// grpc call depending on the method type.
+    fn dispatch_request(
+        messages_model: &mut MessagesModel,
+        sx: Sender<InternalStreamData>,
+    ) -> std::result::Result<JoinHandle<()>, ErrorKind> {
+        let method = messages_model.selected_method.as_ref();
+        let is_client_streaming = method.is_some_and(MethodDescriptor::is_client_streaming);
+        let is_server_streaming = method.is_some_and(MethodDescriptor::is_server_streaming);
+        let connection = messages_model.connection_config();
+        let clients = messages_model.client_cache();
+        let raw = messages_model.request.raw;
+        if is_client_streaming && is_server_streaming {
+            let req = messages_model.collect_request()?;
+            return Ok(tokio::spawn(do_bidi_streaming_request(
+                req, connection, clients, raw, sx,
+            )));
+        }
+        if is_client_streaming {
+            let reqs = messages_model.collect_requests()?;
+            return Ok(tokio::spawn(async move {
+                let resp = do_client_streaming_request(reqs, connection, clients, raw).await;
+                let _ = sx.send(resp.into()).await;
+            }));
+        }
+
+        let req = messages_model.collect_request()?;
+        if is_server_streaming {
+            return Ok(tokio::spawn(do_server_streaming_request(
+                req, connection, clients, raw, sx,
+            )));
+        }
+        Ok(tokio::spawn(async move {
+            let resp = do_request(req, connection, clients, raw).await;
+            let _ = sx.send(resp.into()).await;
+        }))
+    }
+
+    /// Spawns a task which checks the health of the address on the
+    /// headers page.
+    fn dispatch_health_check(&mut self, sx: Sender<InternalStreamData>) {
+        let messages_model = self.ctx.messages.borrow();
+        let result = messages_model.collect_health_request();
+        let connection = messages_model.connection_config();
+        let clients = messages_model.client_cache();
+        let mut headers_model = self.ctx.headers.borrow_mut();
+        match result {
+            Ok(req) => {
+                let method = headers_model.health.method();
+                headers_model.health.handler = Some(tokio::spawn(do_health_check(
+                    req, method, connection, clients, sx,
+                )));
+            }
+            Err(err) => {
+                headers_model.health.status = HealthStatus::Error(err.msg);
+            }
+        }
+    }
+
+    /// Saves the completed response with the autosaved request, to the
+    /// save spot and method of the time the request was sent.
+    fn save_response(messages_model: &mut MessagesModel) {
+        let Some((save_spot, method)) = messages_model.history_spot.take() else {
+            return;
+        };
+        if messages_model.history_model.autosave {
+            messages_model.history_model.save_response(
+                save_spot,
+                &method,
+                &messages_model.response,
+            );
+        }
+    }
+
+    fn handle_internal_event(&mut self, event: &InternalStreamData) {
+        let mut messages_model = self.ctx.messages.borrow_mut();
+        match event {
+            InternalStreamData::Request(result) => {
+                result.set(&mut messages_model.response);
+                messages_model.handler.take();
+                Self::save_response(&mut messages_model);
+            }
+            InternalStreamData::Stream(result) => {
+                result.set(&mut messages_model.response);
+                if result.is_end() {
+                    messages_model.handler.take();
+                    messages_model.session.take();
+                    Self::save_response(&mut messages_model);
+                }
+            }
+            InternalStreamData::Session(event) => {
+                event.set(&mut messages_model);
+            }
+            InternalStreamData::Health(status) => {
+                self.ctx.headers.borrow_mut().health.status = status.clone();
+            }
+        }
     }
 }
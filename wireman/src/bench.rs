@@ -0,0 +1,102 @@
+//! The `bench` command. Load tests a method with the request that is
+//! shown in the app: the history save spot of the method or, if it
+//! is empty, the request template with the default headers.
+use crate::{
+    context::AppContext,
+    model::{headers::parse_timeout, CoreClient},
+};
+use config::{
+    cli::{BenchArgs, BenchFormat},
+    init_from_env,
+};
+use core::features::bench::{BenchConfig, BenchLimit};
+use std::error::Error;
+
+type Result<T> = std::result::Result<T, Box<dyn Error>>;
+
+/// Runs the benchmark and prints the report to stdout.
+pub async fn run(args: BenchArgs) -> Result<()> {
+    let config = bench_config(&args)?;
+    let cfg = init_from_env()?;
+    let ctx = AppContext::new(&cfg).await?;
+
+    let (reqs, connection, clients) = {
+        let mut messages = ctx.messages.borrow_mut();
+        let (service, method) = split_method_name(&args.method)
+            .ok_or_else(|| format!("Invalid method name: {}", args.method))?;
+        let method = messages
+            .request
+            .core_client
+            .borrow()
+            .get_method_by_name(service, method)
+            .ok_or_else(|| format!("Method not found: {}", args.method))?;
+        messages.history_model.select(usize::from(args.spot));
+        messages.load_method(&method);
+        let reqs = if method.is_client_streaming() {
+            messages.collect_requests()
+        } else {
+            messages.collect_request().map(|req| vec![req])
+        }
+        .map_err(|err| err.string())?;
+        (reqs, messages.connection_config(), messages.client_cache())
+    };
+
+    let report = CoreClient::call_bench_async(reqs, connection, &clients, config)
+        .await
+        .map_err(|err| err.string())?;
+    match args.format {
+        BenchFormat::Text => print!("{report}"),
+        BenchFormat::Json => println!("{}", serde_json::to_string_pretty(&report)?),
+    }
+    Ok(())
+}
+
+/// Returns the benchmark settings of the command line arguments.
+fn bench_config(args: &BenchArgs) -> Result<BenchConfig> {
+    let limit = match args.duration.as_deref().map(parse_timeout).transpose()? {
+        Some(Some(duration)) => BenchLimit::Duration(duration),
+        _ => BenchLimit::Requests(args.requests),
+    };
+    Ok(BenchConfig {
+        limit,
+        concurrency: args.concurrency,
+        rate: args.rate,
+    })
+}
+
+/// Splits a full method name such as `helloworld.Greeter/SayHello` or
+/// `helloworld.Greeter.SayHello` into the service and the method name.
+fn split_method_name(name: &str) -> Option<(&str, &str)> {
+    let (service, method) = name.rsplit_once('/').or_else(|| name.rsplit_once('.'))?;
+    Some((service, method)).filter(|(service, method)| !service.is_empty() && !method.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_method_name() {
+        // given
+        let names = [
+            "helloworld.Greeter/SayHello",
+            "helloworld.Greeter.SayHello",
+            "SayHello",
+            "helloworld.Greeter/",
+        ];
+
+        // when
+        let split: Vec<_> = names.iter().map(|name| split_method_name(name)).collect();
+
+        // then
+        assert_eq!(
+            split,
+            vec![
+                Some(("helloworld.Greeter", "SayHello")),
+                Some(("helloworld.Greeter", "SayHello")),
+                None,
+                None,
+            ]
+        );
+    }
+}
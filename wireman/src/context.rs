@@ -33,9 +33,10 @@ pub struct AppContext {
 }
 
 impl AppContext {
-    pub fn new(env: &Config) -> Result<Self> {
+    pub async fn new(env: &Config) -> Result<Self> {
         // The core client
-        let core_client_rc = Rc::new(RefCell::new(CoreClient::new(env)?));
+        let desc = CoreClient::load_descriptor(env).await?;
+        let core_client_rc = Rc::new(RefCell::new(CoreClient::new(env, desc)?));
 
         // The selection model
         let selection = Rc::new(RefCell::new(SelectionModel::new(Rc::clone(
@@ -44,7 +45,13 @@ impl AppContext {
 
         // The metadata model
         let server_address = &core_client_rc.borrow().get_default_address();
-        let headers = Rc::new(RefCell::new(HeadersModel::new(server_address)));
+        let timeout = core_client_rc.borrow().get_default_timeout();
+        let transport = core_client_rc.borrow().get_transport(server_address);
+        let headers = Rc::new(RefCell::new(HeadersModel::new(
+            server_address,
+            timeout,
+            transport,
+        )));
 
         // The history model
         let history_model = HistoryModel::new(env)?;
@@ -179,17 +179,31 @@ impl MessagesInput<'_> {
             KeyCode::Up if tab == MessagesTab::Response && !self.ctx.disable_root_events => {
                 self.ctx.messages_tab = MessagesTab::Request;
             }
+            KeyCode::Enter
+                if tab == MessagesTab::Request
+                    && !self.ctx.disable_root_events
+                    && self.model.borrow().is_session_open() =>
+            {
+                self.model.borrow_mut().send_session_message();
+            }
             KeyCode::Enter if tab == MessagesTab::Request && !self.ctx.disable_root_events => {
+                // Start first, so that the metrics of the previous
+                // response are not saved with the request
+                self.model.borrow_mut().start_request();
                 if self.model.borrow().history_model.autosave {
                     self.model.borrow().history_model.save(&self.model.borrow());
                 }
-                self.model.borrow_mut().start_request();
             }
             KeyCode::Char('y')
                 if modifier == KeyModifiers::CONTROL && !self.ctx.disable_root_events =>
             {
                 self.model.borrow_mut().yank_grpcurl();
             }
+            KeyCode::Char('e')
+                if modifier == KeyModifiers::CONTROL && !self.ctx.disable_root_events =>
+            {
+                self.model.borrow_mut().close_session();
+            }
             KeyCode::Char('f')
                 if modifier == KeyModifiers::CONTROL
                     && tab == MessagesTab::Request
@@ -198,6 +212,13 @@ impl MessagesInput<'_> {
                 let request = &mut self.model.borrow_mut().request.editor;
                 request.format_json();
             }
+            KeyCode::Char('r')
+                if modifier == KeyModifiers::CONTROL
+                    && tab == MessagesTab::Request
+                    && !self.ctx.disable_root_events =>
+            {
+                self.model.borrow_mut().toggle_raw_format();
+            }
             KeyCode::Char('d')
                 if modifier == KeyModifiers::CONTROL && !self.ctx.disable_root_events =>
             {
@@ -299,7 +320,24 @@ impl HeadersInput<'_> {
             _ => {
                 let selected = model.selected.clone();
                 match selected {
-                    HeadersSelection::Addr => model.addr.on_key(event, true),
+                    HeadersSelection::Addr => match event.code {
+                        KeyCode::Char('t') if event.modifiers == KeyModifiers::CONTROL => {
+                            model.toggle_transport();
+                        }
+                        _ => {
+                            let address = model.address();
+                            model.addr.on_key(event, true);
+                            // The transport follows the config of the new address
+                            if model.address() != address {
+                                let messages = self.ctx.messages.borrow();
+                                let core_client = messages.request.core_client.borrow();
+                                model.transport = core_client.get_transport(&model.address());
+                            }
+                        }
+                    },
+                    HeadersSelection::Timeout => model.timeout.on_key(event, true),
+                    HeadersSelection::Tls => model.tls.on_key(event),
+                    HeadersSelection::Health => model.health.on_key(event),
                     HeadersSelection::Auth => model.auth.on_key(event),
                     HeadersSelection::Meta => model.meta.on_key(event),
                     HeadersSelection::None => match event.code {
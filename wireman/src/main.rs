@@ -1,14 +1,19 @@
 #![allow(clippy::cast_possible_truncation, clippy::module_name_repetitions)]
 #![allow(dead_code)]
 mod app;
+mod bench;
 mod context;
 mod input;
+mod mock;
 mod model;
 mod term;
 mod view;
 mod widgets;
 use app::App;
-use config::{cli, init_from_env};
+use config::{
+    cli::{self, Command},
+    init_from_env,
+};
 use std::{env, error::Error};
 
 type Result<T> = std::result::Result<T, Box<dyn Error>>;
@@ -16,7 +21,11 @@ type Result<T> = std::result::Result<T, Box<dyn Error>>;
 #[tokio::main]
 async fn main() -> Result<()> {
     if env::args_os().count() > 1 {
-        cli::parse();
+        match cli::parse() {
+            Command::Check => cli::check(),
+            Command::Bench(args) => bench::run(args).await?,
+            Command::Mock(args) => mock::run(args).await?,
+        }
         return Ok(());
     }
 
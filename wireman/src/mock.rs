@@ -0,0 +1,99 @@
+//! The `mock` command. Serves all services of the protos on a local
+//! port. Requests are answered by the rules files, by the responses
+//! saved with the history save spot of a method or by the template of
+//! the response message.
+use crate::context::AppContext;
+use config::{cli::MockArgs, init_from_env};
+use core::features::mock::{Mock, MockRule};
+use http::Uri;
+use std::error::Error;
+use tokio::net::TcpListener;
+
+type Result<T> = std::result::Result<T, Box<dyn Error>>;
+
+/// Runs the mock server until it is stopped.
+pub async fn run(args: MockArgs) -> Result<()> {
+    let cfg = init_from_env()?;
+    let ctx = AppContext::new(&cfg).await?;
+
+    let (mock, address, services) = {
+        let messages = ctx.messages.borrow();
+        let core_client = messages.request.core_client.borrow();
+
+        let mut rules = Vec::new();
+        for file in &args.rules {
+            rules.extend(MockRule::from_file(file).map_err(|err| format!("{file}: {err}"))?);
+        }
+        let mut mock = Mock::new(core_client.get_descriptor().clone()).with_rules(rules)?;
+
+        let mut services = Vec::new();
+        for service in core_client.get_services() {
+            for method in core_client.get_methods(&service) {
+                let responses = messages
+                    .history_model
+                    .responses(usize::from(args.spot), &method);
+                let (with_responses, errors) = mock.with_saved_responses(&method, &responses);
+                for err in errors {
+                    eprintln!("Skipping a saved response: {err}");
+                }
+                mock = with_responses;
+            }
+            services.push(service.full_name().to_string());
+        }
+
+        let address = match args.address {
+            Some(address) => address,
+            None => listen_address(&core_client.get_default_address())
+                .ok_or("Cannot listen on the default address, use --address")?,
+        };
+        (mock, address, services)
+    };
+
+    let listener = TcpListener::bind(&address).await?;
+    println!("Serving mock on {}", listener.local_addr()?);
+    for service in services {
+        println!("  {service}");
+    }
+    mock.serve(listener).await?;
+    Ok(())
+}
+
+/// Returns the host and port of an address such as
+/// `http://localhost:50051`.
+fn listen_address(address: &str) -> Option<String> {
+    let uri = address.parse::<Uri>().ok()?;
+    let host = uri.host()?;
+    let port = uri.port_u16().unwrap_or(match uri.scheme_str() {
+        Some("https") => 443,
+        _ => 80,
+    });
+    Some(format!("{host}:{port}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_listen_address() {
+        // given
+        let addresses = [
+            "http://localhost:50051",
+            "https://example.com",
+            "unix:///tmp/grpc.sock",
+        ];
+
+        // when
+        let listen: Vec<_> = addresses.iter().map(|a| listen_address(a)).collect();
+
+        // then
+        assert_eq!(
+            listen,
+            vec![
+                Some("localhost:50051".to_string()),
+                Some("example.com:443".to_string()),
+                None,
+            ]
+        );
+    }
+}
@@ -1,12 +1,31 @@
 use crate::widgets::editor::ErrorKind;
 use config::Config;
 use core::{
+    client::{
+        compression::Compression,
+        connection::ConnectionConfig,
+        endpoint::EndpointConfig,
+        health::{HealthWatch, ServingStatus},
+        proxy::ProxyConfig,
+        service_config::ServiceConfig,
+        tls::TlsConfig,
+        transport::Transport,
+        GrpcClient, ResponseStream, SessionSender,
+    },
     descriptor::{RequestMessage, ResponseMessage},
-    features::grpcurl,
+    features::{
+        bench::{bench, BenchConfig, BenchReport},
+        grpcurl,
+    },
     MethodDescriptor, ProtoDescriptor, ServiceDescriptor,
 };
-use http::Uri;
-use std::{collections::HashMap, error::Error};
+use std::{
+    collections::HashMap,
+    error::Error,
+    future::Future,
+    sync::{Arc, Mutex, PoisonError},
+    time::Duration,
+};
 
 /// The [`CoreClient`] calls the proto descriptor and grpc client of the
 /// core package.  
@@ -16,22 +35,76 @@ pub struct CoreClient {
     desc: ProtoDescriptor,
     /// Config to create a new grpc client
     grpc: GrpcClientConfig,
+    /// The grpc clients which are reused across calls
+    clients: ClientCache,
 }
 
 impl Default for CoreClient {
     fn default() -> Self {
-        Self::new(&Config::default()).unwrap()
+        Self::new(&Config::default(), ProtoDescriptor::default()).unwrap()
     }
 }
 
 #[derive(Debug, Clone)]
 struct GrpcClientConfig(Config);
 
+/// The timeout of the server reflection at startup, if the config
+/// defines neither a call nor a connect timeout.
+const REFLECTION_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Loads the protos from the server reflection service of the default
+/// address. Times out after the call timeout or the connect timeout of
+/// the config.
+async fn reflect_descriptor(cfg: &Config) -> Result<ProtoDescriptor, Box<dyn Error>> {
+    let timeout = cfg
+        .server
+        .timeout()
+        .or_else(|| cfg.server.connect_timeout())
+        .unwrap_or(REFLECTION_TIMEOUT);
+    let address = &cfg.server.default_address;
+    let reflection = ProtoDescriptor::from_reflection(address, connection_config(cfg));
+    Ok(tokio::time::timeout(timeout, reflection).await??)
+}
+
 impl CoreClient {
-    pub fn new(cfg: &Config) -> Result<Self, Box<dyn Error>> {
-        let desc = ProtoDescriptor::new(cfg.includes(), cfg.files())?;
+    /// Loads the protos of the config. With server reflection enabled,
+    /// the protos of the server are merged with the configured files and
+    /// descriptor sets. If the reflection fails or times out, only the
+    /// files and descriptor sets are loaded.
+    pub async fn load_descriptor(cfg: &Config) -> Result<ProtoDescriptor, Box<dyn Error>> {
+        let mut desc = if cfg.server.reflection {
+            reflect_descriptor(cfg).await.unwrap_or_else(|err| {
+                eprintln!("Failed to load the protos from the server reflection: {err}");
+                ProtoDescriptor::default()
+            })
+        } else {
+            ProtoDescriptor::default()
+        };
+        let files = cfg.files();
+        if !files.is_empty() {
+            desc.add_files(cfg.includes(), files)?;
+        }
+        desc.add_descriptor_sets(cfg.descriptor_sets())?;
+        Ok(desc)
+    }
+
+    pub fn new(cfg: &Config, desc: ProtoDescriptor) -> Result<Self, Box<dyn Error>> {
+        let service_config = cfg
+            .server
+            .service_config()
+            .map(ServiceConfig::from_file)
+            .transpose()?;
         let grpc = GrpcClientConfig(cfg.clone());
-        Ok(Self { desc, grpc })
+        Ok(Self {
+            desc,
+            grpc,
+            clients: ClientCache::new(service_config),
+        })
+    }
+
+    /// Returns the proto descriptor
+    pub fn get_descriptor(&self) -> &ProtoDescriptor {
+        &self.desc
     }
 
     /// Return the proto Services
@@ -70,16 +143,150 @@ impl CoreClient {
         self.grpc.0.server.default_address.clone()
     }
 
+    /// Returns the default timeout as defined in the config
+    pub fn get_default_timeout(&self) -> Option<Duration> {
+        self.grpc.0.server.timeout()
+    }
+
+    /// Returns the protocol which is spoken with the given address as
+    /// defined in the config
+    pub fn get_transport(&self, address: &str) -> Transport {
+        match self.grpc.0.server.transport(address) {
+            config::config::Transport::Grpc => Transport::Grpc,
+            config::config::Transport::GrpcWeb => Transport::GrpcWeb,
+            config::config::Transport::Connect => Transport::Connect,
+            config::config::Transport::ConnectJson => Transport::ConnectJson,
+        }
+    }
+
+    /// Returns the compression of the calls to the given address as
+    /// defined in the config
+    pub fn get_compression(&self, address: &str) -> Compression {
+        let compression = self.grpc.0.server.compression(address);
+        Compression {
+            send_gzip: compression.send_gzip,
+            accept_gzip: compression.accept_gzip,
+        }
+    }
+
+    /// Returns the connection settings as defined in the config. The
+    /// tls settings are applied to calls to `https` addresses.
+    pub fn get_connection_config(&self) -> Option<ConnectionConfig> {
+        connection_config(&self.grpc.0)
+    }
+
     /// Makes a unary grpc call with a given Message and Method which is
     /// defined in [`ProtoMessage`]
-    pub fn call_unary(req: &RequestMessage) -> Result<ResponseMessage, ErrorKind> {
-        Ok(core::client::call_unary_blocking(req)?)
+    pub fn call_unary(
+        req: &RequestMessage,
+        connection: Option<ConnectionConfig>,
+    ) -> Result<ResponseMessage, ErrorKind> {
+        Ok(core::client::call_unary_blocking(req, connection)?)
+    }
+
+    /// Returns the cache of the grpc clients. It is shared by all
+    /// clones of the cache.
+    pub fn client_cache(&self) -> ClientCache {
+        self.clients.clone()
     }
 
     /// Makes a unary grpc call with a given Message and Method which is
     /// defined in [`ProtoMessage`]
-    pub async fn call_unary_async(req: &RequestMessage) -> Result<ResponseMessage, ErrorKind> {
-        Ok(core::client::call_unary_async(req).await?)
+    pub async fn call_unary_async(
+        req: &RequestMessage,
+        connection: Option<ConnectionConfig>,
+        clients: &ClientCache,
+    ) -> Result<ResponseMessage, ErrorKind> {
+        let (key, mut client) = clients.get(req, connection)?;
+        let result = client.unary(req).await;
+        with_attempts(clients.evict_on_failure(&key, result), client.attempts())
+    }
+
+    /// Opens a server streaming grpc call with a given Message and Method
+    /// which is defined in [`ProtoMessage`]
+    pub async fn call_server_streaming_async(
+        req: &RequestMessage,
+        connection: Option<ConnectionConfig>,
+        clients: &ClientCache,
+    ) -> Result<ResponseStream, ErrorKind> {
+        let (key, mut client) = clients.get(req, connection)?;
+        let result = client.server_streaming(req).await;
+        with_attempts(clients.evict_on_failure(&key, result), client.attempts())
+    }
+
+    /// Makes a client streaming grpc call with a list of Messages
+    /// which are sent in order
+    pub async fn call_client_streaming_async(
+        reqs: Vec<RequestMessage>,
+        connection: Option<ConnectionConfig>,
+        clients: &ClientCache,
+    ) -> Result<ResponseMessage, ErrorKind> {
+        let Some(first) = reqs.first() else {
+            return Err(ErrorKind::default_error("No request messages"));
+        };
+        let (key, mut client) = clients.get(first, connection)?;
+        let result = client.client_streaming(reqs).await;
+        with_attempts(clients.evict_on_failure(&key, result), client.attempts())
+    }
+
+    /// Opens a bidirectional streaming session with a given Message
+    /// which is sent as the first message of the session. Returns the
+    /// sending half of the session and the response stream, which opens
+    /// once the server sent its headers.
+    pub async fn call_bidi_streaming_async(
+        req: &RequestMessage,
+        connection: Option<ConnectionConfig>,
+        clients: &ClientCache,
+    ) -> Result<
+        (
+            SessionSender,
+            impl Future<Output = Result<ResponseStream, ErrorKind>>,
+        ),
+        ErrorKind,
+    > {
+        let (key, mut client) = clients.get(req, connection)?;
+        let result = client.bidi_streaming(req).await;
+        let (sender, response) = clients.evict_on_failure(&key, result)?.into_parts();
+        let clients = clients.clone();
+        let response = async move { Ok(clients.evict_on_failure(&key, response.await)?) };
+        Ok((sender, response))
+    }
+
+    /// Checks the health of the address of a `grpc.health.v1` request
+    pub async fn call_health_check_async(
+        req: &RequestMessage,
+        connection: Option<ConnectionConfig>,
+        clients: &ClientCache,
+    ) -> Result<ServingStatus, ErrorKind> {
+        let (key, mut client) = clients.get(req, connection)?;
+        let result = client.health_check(req).await;
+        with_attempts(clients.evict_on_failure(&key, result), client.attempts())
+    }
+
+    /// Watches the health of the address of a `grpc.health.v1` request
+    pub async fn call_health_watch_async(
+        req: &RequestMessage,
+        connection: Option<ConnectionConfig>,
+        clients: &ClientCache,
+    ) -> Result<HealthWatch, ErrorKind> {
+        let (key, mut client) = clients.get(req, connection)?;
+        let result = client.health_watch(req).await;
+        with_attempts(clients.evict_on_failure(&key, result), client.attempts())
+    }
+
+    /// Load tests the method of the requests. The calls share the
+    /// connection of the cached client.
+    pub async fn call_bench_async(
+        reqs: Vec<RequestMessage>,
+        connection: Option<ConnectionConfig>,
+        clients: &ClientCache,
+        config: BenchConfig,
+    ) -> Result<BenchReport, ErrorKind> {
+        let Some(first) = reqs.first() else {
+            return Err(ErrorKind::default_error("No request messages"));
+        };
+        let (_, client) = clients.get(first, connection)?;
+        Ok(bench(client, reqs, config).await?)
     }
 
     /// Return a grpcurl request
@@ -89,13 +296,249 @@ impl CoreClient {
         method_desc: &MethodDescriptor,
         metadata: &HashMap<String, String>,
         address: &str,
+        tls: Option<&TlsConfig>,
     ) -> Result<String, String> {
-        Ok(grpcurl(
+        grpcurl(
             &self.grpc.0.includes,
-            Uri::try_from(address).map_err(|_| "Failed to parse address")?,
+            address,
             message,
             method_desc,
             metadata,
-        ))
+            tls,
+        )
+        .map_err(|err| err.to_string())
+    }
+}
+
+/// Caches the grpc clients by their connection settings. Calls with the
+/// same settings reuse the connection of the cached client instead of
+/// doing a new handshake.
+#[derive(Debug, Clone, Default)]
+pub struct ClientCache {
+    clients: Arc<Mutex<HashMap<ClientKey, GrpcClient>>>,
+    /// The timeouts and retry policies which are applied to all clients
+    service_config: Option<Arc<ServiceConfig>>,
+}
+
+/// The connection settings of a grpc client.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct ClientKey {
+    address: String,
+    connection: Option<ConnectionConfig>,
+    transport: Transport,
+}
+
+impl ClientCache {
+    /// Returns an empty cache. The service config is applied to all
+    /// clients.
+    fn new(service_config: Option<ServiceConfig>) -> Self {
+        Self {
+            clients: Arc::default(),
+            service_config: service_config.map(Arc::new),
+        }
+    }
+
+    /// Returns the cached client for the connection settings of the
+    /// request. Creates and caches a new client if there is none. The
+    /// compression of the request is applied to the returned client only.
+    fn get(
+        &self,
+        req: &RequestMessage,
+        connection: Option<ConnectionConfig>,
+    ) -> Result<(ClientKey, GrpcClient), core::error::Error> {
+        let key = ClientKey {
+            address: req.address().to_string(),
+            connection: connection.clone(),
+            transport: req.transport(),
+        };
+        let mut clients = self.clients.lock().unwrap_or_else(PoisonError::into_inner);
+        if let Some(client) = clients.get(&key) {
+            return Ok((key, client.clone().with_compression(req.compression())));
+        }
+        let mut client = GrpcClient::from_address(req.address(), connection, req.transport())?;
+        if let Some(service_config) = &self.service_config {
+            client = client.with_service_config(service_config.clone());
+        }
+        clients.insert(key.clone(), client.clone());
+        Ok((key, client.with_compression(req.compression())))
+    }
+
+    /// Drops the cached client if the call failed because the connection
+    /// is broken. The next call connects a new client.
+    fn evict_on_failure<T>(
+        &self,
+        key: &ClientKey,
+        result: Result<T, core::error::Error>,
+    ) -> Result<T, core::error::Error> {
+        if result
+            .as_ref()
+            .is_err_and(core::error::Error::is_unavailable)
+        {
+            let mut clients = self.clients.lock().unwrap_or_else(PoisonError::into_inner);
+            clients.remove(key);
+        }
+        result
+    }
+
+    /// Returns the number of cached clients.
+    #[cfg(test)]
+    fn len(&self) -> usize {
+        self.clients.lock().unwrap().len()
+    }
+}
+
+/// Converts the result of a call. A failed call which was retried
+/// reports the number of attempts.
+fn with_attempts<T>(result: Result<T, core::error::Error>, attempts: u32) -> Result<T, ErrorKind> {
+    result.map_err(|err| {
+        let mut err = ErrorKind::from(err);
+        if attempts > 1 {
+            err.msg = format!("{}\n\nFailed after {attempts} attempts", err.msg);
+        }
+        err
+    })
+}
+
+/// Maps the [tls] section, the proxy and the connection tuning of the
+/// config to the connection settings of the grpc client. Returns `None`
+/// if no custom settings are given, in which case `https` addresses use
+/// the default webpki roots.
+fn connection_config(cfg: &Config) -> Option<ConnectionConfig> {
+    let tls = &cfg.tls;
+    let proxy = proxy_config(cfg);
+    let endpoint = endpoint_config(cfg);
+    if *tls == config::config::TlsConfig::default()
+        && proxy.is_empty()
+        && endpoint == EndpointConfig::default()
+    {
+        return None;
+    }
+    let tls =
+        TlsConfig::new(tls.custom_cert()).with_client_auth(tls.client_cert(), tls.client_key());
+    Some(
+        ConnectionConfig::new(tls)
+            .with_proxy(proxy)
+            .with_endpoint(endpoint),
+    )
+}
+
+/// Returns the tuning of the connections of the config.
+fn endpoint_config(cfg: &Config) -> EndpointConfig {
+    let server = &cfg.server;
+    EndpointConfig {
+        connect_timeout: server.connect_timeout(),
+        keepalive_interval: server.keepalive_interval(),
+        keepalive_timeout: server.keepalive_timeout(),
+        initial_stream_window_size: server.initial_stream_window_size,
+        initial_connection_window_size: server.initial_connection_window_size,
+        tcp_nodelay: server.tcp_nodelay,
+        max_decoding_message_size: server.max_decoding_message_size,
+        max_encoding_message_size: server.max_encoding_message_size,
+    }
+}
+
+/// Returns the proxy of the config, or of the environment if the config
+/// sets none. The `NO_PROXY` variable applies in both cases.
+fn proxy_config(cfg: &Config) -> ProxyConfig {
+    let env = ProxyConfig::from_env();
+    match cfg.server.proxy() {
+        Some(proxy) => ProxyConfig::new(Some(proxy)).with_no_proxy(env.no_proxy().to_vec()),
+        None => env,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use config::config::ServerConfig;
+
+    fn test_request(address: &str) -> RequestMessage {
+        let desc =
+            ProtoDescriptor::new(vec!["../wireman-core"], vec!["test_files/test.proto"]).unwrap();
+        let method = desc
+            .get_method_by_name("proto.TestService", "Simple")
+            .unwrap();
+        let mut req = desc.get_request(&method);
+        req.set_address(address);
+        req
+    }
+
+    /// Returns a runtime for the tests. The `tokio::test` macro can not be
+    /// used, since the `core` crate shadows the core library.
+    fn runtime() -> tokio::runtime::Runtime {
+        tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_client_cache_reuses_clients() {
+        // given
+        let rt = runtime();
+        let _guard = rt.enter();
+        let clients = ClientCache::default();
+        let req = test_request("http://localhost:50051");
+        let mut other = test_request("http://localhost:50052");
+
+        // when
+        clients.get(&req, None).unwrap();
+        clients.get(&req, None).unwrap();
+        clients.get(&other, None).unwrap();
+        other.set_transport(Transport::GrpcWeb);
+        clients.get(&other, None).unwrap();
+        other.set_compression(Compression {
+            send_gzip: true,
+            accept_gzip: true,
+        });
+        clients.get(&other, None).unwrap();
+
+        // then
+        assert_eq!(clients.len(), 3);
+    }
+
+    #[test]
+    fn test_load_descriptor_reflection_timeout() {
+        // given
+        let rt = runtime();
+        // A server which accepts connections but never responds
+        let listener = rt.block_on(tokio::net::TcpListener::bind("127.0.0.1:0"));
+        let listener = listener.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let cfg = Config {
+            includes: vec![String::from("../wireman-core")],
+            files: vec![String::from("test_files/test.proto")],
+            server: ServerConfig {
+                default_address: format!("http://{addr}"),
+                reflection: true,
+                timeout_ms: Some(100),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        // when
+        let desc = rt.block_on(CoreClient::load_descriptor(&cfg)).unwrap();
+
+        // then
+        assert!(desc.get_service_by_name("proto.TestService").is_some());
+    }
+
+    #[test]
+    fn test_client_cache_evicts_unavailable() {
+        // given
+        let rt = runtime();
+        let _guard = rt.enter();
+        let clients = ClientCache::default();
+        let req = test_request("http://127.0.0.1:1");
+        let (key, mut client) = clients.get(&req, None).unwrap();
+
+        // when
+        let result = rt.block_on(client.unary(&req));
+        let result = clients.evict_on_failure(&key, result);
+
+        // then
+        assert!(result.is_err_and(|err| err.is_unavailable()));
+        assert_eq!(clients.len(), 0);
     }
 }
@@ -1,10 +1,15 @@
 pub mod auth;
 pub use auth::{AuthHeader, AuthSelection};
+pub mod health;
+pub use health::{HealthHeader, HealthStatus};
 pub mod meta;
+pub mod tls;
 use crate::widgets::editor::TextEditor;
+use core::client::transport::Transport;
 use edtui::EditorMode;
 pub use meta::MetaHeaders;
-use std::{collections::HashMap, process::Command};
+use std::{collections::HashMap, process::Command, time::Duration};
+pub use tls::TlsHeader;
 
 /// The data model for the `gRPC` headers. Contains authorization
 /// headers and metadata key value headers.
@@ -12,32 +17,60 @@ pub struct HeadersModel {
     /// The host address.
     pub addr: TextEditor,
 
+    /// The protocol which is spoken with the host.
+    pub transport: Transport,
+
+    /// The timeout of the call.
+    pub timeout: TextEditor,
+
+    /// The TLS settings.
+    pub tls: TlsHeader,
+
     /// The authentication header.
     pub auth: AuthHeader,
 
     /// The metadata headers.
     pub meta: MetaHeaders,
 
+    /// The health check of the address.
+    pub health: HealthHeader,
+
     /// The selection state.
     pub selected: HeadersSelection,
+
+    /// The error of an invalid header, which refused the last request.
+    pub error: Option<String>,
 }
 
 impl Default for HeadersModel {
     fn default() -> Self {
-        Self::new("")
+        Self::new("", None, Transport::default())
     }
 }
 
 impl HeadersModel {
     /// Create a new `HeadersModel` instance
-    pub fn new(default_address: &str) -> Self {
+    pub fn new(
+        default_address: &str,
+        default_timeout: Option<Duration>,
+        transport: Transport,
+    ) -> Self {
         let mut address = TextEditor::new();
         address.set_text_raw(default_address);
+        let mut timeout = TextEditor::new();
+        if let Some(default_timeout) = default_timeout {
+            timeout.set_text_raw(&format_timeout(default_timeout));
+        }
         Self {
             addr: address,
+            transport,
+            timeout,
+            tls: TlsHeader::default(),
             auth: AuthHeader::default(),
             meta: MetaHeaders::default(),
+            health: HealthHeader::default(),
             selected: HeadersSelection::default(),
+            error: None,
         }
     }
     /// Get the address as a string
@@ -45,12 +78,37 @@ impl HeadersModel {
         self.addr.get_text_raw()
     }
 
+    /// Switches to the next transport.
+    pub fn toggle_transport(&mut self) {
+        self.transport = match self.transport {
+            Transport::Grpc => Transport::GrpcWeb,
+            Transport::GrpcWeb => Transport::Connect,
+            Transport::Connect => Transport::ConnectJson,
+            Transport::ConnectJson => Transport::Grpc,
+        };
+    }
+
+    /// Get the timeout of the call. Returns `None` if no timeout is set.
+    ///
+    /// # Errors
+    /// - The timeout is not a valid duration
+    pub fn timeout(&self) -> Result<Option<Duration>, String> {
+        parse_timeout(&self.timeout.get_text_raw())
+    }
+
     /// Returns the editor mode
     pub fn mode(&self) -> EditorMode {
-        [self.auth.mode(), self.addr.state.mode, self.meta.mode()]
-            .into_iter()
-            .find(|&x| x != EditorMode::Normal)
-            .unwrap_or(EditorMode::Normal)
+        [
+            self.auth.mode(),
+            self.addr.state.mode,
+            self.timeout.state.mode,
+            self.tls.mode(),
+            self.meta.mode(),
+            self.health.mode(),
+        ]
+        .into_iter()
+        .find(|&x| x != EditorMode::Normal)
+        .unwrap_or(EditorMode::Normal)
     }
 
     /// Get the raw headers as a map
@@ -94,7 +152,10 @@ impl HeadersModel {
     pub fn next(&mut self) -> HeadersSelection {
         match self.selected {
             HeadersSelection::None => HeadersSelection::Addr,
-            HeadersSelection::Addr => HeadersSelection::Auth,
+            HeadersSelection::Addr => HeadersSelection::Timeout,
+            HeadersSelection::Timeout => HeadersSelection::Tls,
+            HeadersSelection::Tls => HeadersSelection::Health,
+            HeadersSelection::Health => HeadersSelection::Auth,
             HeadersSelection::Auth => {
                 if self.meta.is_hidden() {
                     return HeadersSelection::Addr;
@@ -113,7 +174,10 @@ impl HeadersModel {
     /// TODO: Simplify
     pub fn prev(&mut self) -> HeadersSelection {
         match self.selected {
-            HeadersSelection::None | HeadersSelection::Auth => HeadersSelection::Addr,
+            HeadersSelection::None | HeadersSelection::Timeout => HeadersSelection::Addr,
+            HeadersSelection::Tls => HeadersSelection::Timeout,
+            HeadersSelection::Health => HeadersSelection::Tls,
+            HeadersSelection::Auth => HeadersSelection::Health,
             HeadersSelection::Addr => {
                 if self.meta.is_hidden() {
                     return HeadersSelection::Auth;
@@ -130,9 +194,13 @@ impl HeadersModel {
 
     /// Clears the headers state.
     pub fn clear(&mut self) {
+        self.timeout.clear();
+        self.tls.clear();
         self.auth.clear();
         self.meta.clear();
+        self.health.clear();
         self.selected = HeadersSelection::None;
+        self.error = None;
     }
 }
 
@@ -142,10 +210,48 @@ pub enum HeadersSelection {
     #[default]
     None,
     Addr,
+    Timeout,
+    Tls,
+    Health,
     Auth,
     Meta,
 }
 
+/// Parses a timeout such as `500ms`, `5s` or `1m`. A number without
+/// unit is interpreted as seconds. An empty string means no timeout.
+pub(crate) fn parse_timeout(raw: &str) -> Result<Option<Duration>, String> {
+    let raw = raw.trim();
+    if raw.is_empty() {
+        return Ok(None);
+    }
+    let (value, unit) = raw
+        .find(|c: char| c.is_ascii_alphabetic())
+        .map_or((raw, ""), |i| raw.split_at(i));
+    let value: f64 = value
+        .trim()
+        .parse()
+        .map_err(|_| format!("Invalid timeout: {raw}"))?;
+    let secs = match unit {
+        "ms" => value / 1000.0,
+        "" | "s" => value,
+        "m" => value * 60.0,
+        _ => return Err(format!("Invalid timeout unit: {unit}")),
+    };
+    Duration::try_from_secs_f64(secs)
+        .map(Some)
+        .map_err(|_| format!("Invalid timeout: {raw}"))
+}
+
+/// Formats a timeout in the format that is accepted by [`parse_timeout`].
+fn format_timeout(timeout: Duration) -> String {
+    let millis = timeout.as_millis();
+    if millis % 1000 == 0 {
+        format!("{}s", millis / 1000)
+    } else {
+        format!("{millis}ms")
+    }
+}
+
 fn try_expand(raw: &str) -> String {
     if raw.starts_with("$(") && raw.ends_with(')') {
         let command = &raw[2..raw.len() - 1];
@@ -173,3 +279,26 @@ fn execute_command(command: &str) -> Option<String> {
     };
     None
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_timeout() {
+        assert_eq!(parse_timeout(""), Ok(None));
+        assert_eq!(parse_timeout("500ms"), Ok(Some(Duration::from_millis(500))));
+        assert_eq!(parse_timeout("5s"), Ok(Some(Duration::from_secs(5))));
+        assert_eq!(parse_timeout("1.5"), Ok(Some(Duration::from_millis(1500))));
+        assert_eq!(parse_timeout("2m"), Ok(Some(Duration::from_secs(120))));
+        assert!(parse_timeout("5h").is_err());
+        assert!(parse_timeout("-1s").is_err());
+        assert!(parse_timeout("abc").is_err());
+    }
+
+    #[test]
+    fn test_format_timeout() {
+        assert_eq!(format_timeout(Duration::from_secs(5)), "5s");
+        assert_eq!(format_timeout(Duration::from_millis(1500)), "1500ms");
+    }
+}
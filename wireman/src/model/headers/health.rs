@@ -0,0 +1,207 @@
+use crate::{
+    app::InternalStreamData,
+    model::core_client::{ClientCache, CoreClient},
+    widgets::editor::{ErrorKind, TextEditor},
+};
+use core::{
+    client::{
+        connection::ConnectionConfig,
+        health::{HealthMethod, ServingStatus},
+    },
+    descriptor::RequestMessage,
+};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use edtui::EditorMode;
+use tokio::{sync::mpsc::Sender, task::JoinHandle};
+
+/// The `grpc.health.v1` health check of the address.
+#[derive(Default)]
+pub struct HealthHeader {
+    /// The service to check, empty for the whole server.
+    pub(crate) service: TextEditor,
+    /// Whether the status is watched instead of checked once.
+    pub(crate) watch: bool,
+    /// The result of the last check.
+    pub(crate) status: HealthStatus,
+    /// Whether a check is started on the next frame.
+    pub(crate) dispatch: bool,
+    /// The task of the running check.
+    pub(crate) handler: Option<JoinHandle<()>>,
+}
+
+/// The result of a health check.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum HealthStatus {
+    /// No check was made.
+    #[default]
+    None,
+    /// The check is running.
+    Checking,
+    /// The serving status reported by the server.
+    Status(ServingStatus),
+    /// The check failed.
+    Error(String),
+    /// The server closed the watch stream.
+    Closed,
+}
+
+impl HealthStatus {
+    /// Returns the status of a failed check. Only the first line of the
+    /// error is kept, the rich error details are dropped.
+    fn error(err: &ErrorKind) -> Self {
+        Self::Error(err.msg.lines().next().unwrap_or_default().to_string())
+    }
+}
+
+impl HealthHeader {
+    pub fn toggle_watch(&mut self) {
+        self.watch = !self.watch;
+    }
+
+    pub fn on_key(&mut self, event: KeyEvent) {
+        match event.code {
+            KeyCode::Char('t') if event.modifiers == KeyModifiers::CONTROL => {
+                self.toggle_watch();
+            }
+            KeyCode::Enter if self.mode() == EditorMode::Normal => {
+                if self.is_watching() {
+                    self.stop();
+                } else {
+                    self.start();
+                }
+            }
+            KeyCode::Left | KeyCode::Char('h') | KeyCode::Right | KeyCode::Char('l')
+                if self.mode() == EditorMode::Normal && self.service.is_empty() =>
+            {
+                self.toggle_watch();
+            }
+            _ => self.service.on_key(event, true),
+        }
+    }
+
+    pub fn mode(&self) -> EditorMode {
+        self.service.state.mode
+    }
+
+    /// Returns the health method of the selected mode.
+    pub fn method(&self) -> HealthMethod {
+        if self.watch {
+            HealthMethod::Watch
+        } else {
+            HealthMethod::Check
+        }
+    }
+
+    /// Returns the service to check.
+    pub fn service(&self) -> String {
+        self.service.get_text_raw()
+    }
+
+    /// Starts a check on the next frame. A running check is stopped.
+    pub fn start(&mut self) {
+        self.stop();
+        self.dispatch = true;
+        self.status = HealthStatus::Checking;
+    }
+
+    /// Stops a running check. The last status is kept.
+    pub fn stop(&mut self) {
+        if let Some(handler) = self.handler.take() {
+            handler.abort();
+        }
+        if self.status == HealthStatus::Checking {
+            self.status = HealthStatus::None;
+        }
+    }
+
+    /// Whether a watch stream is open.
+    pub fn is_watching(&self) -> bool {
+        self.watch
+            && self
+                .handler
+                .as_ref()
+                .is_some_and(|handler| !handler.is_finished())
+    }
+
+    pub(super) fn clear(&mut self) {
+        self.stop();
+        self.service.clear();
+        self.watch = false;
+        self.status = HealthStatus::None;
+    }
+}
+
+/// Checks the health of the address of the request. The serving status
+/// is sent once for a `Check`, and on every change for a `Watch`.
+pub async fn do_health_check(
+    req: RequestMessage,
+    method: HealthMethod,
+    connection: Option<ConnectionConfig>,
+    clients: ClientCache,
+    sx: Sender<InternalStreamData>,
+) {
+    if method == HealthMethod::Check {
+        let status = match CoreClient::call_health_check_async(&req, connection, &clients).await {
+            Ok(status) => HealthStatus::Status(status),
+            Err(err) => HealthStatus::error(&err),
+        };
+        let _ = sx.send(status.into()).await;
+        return;
+    }
+    let mut watch = match CoreClient::call_health_watch_async(&req, connection, &clients).await {
+        Ok(watch) => watch,
+        Err(err) => {
+            let _ = sx.send(HealthStatus::error(&err).into()).await;
+            return;
+        }
+    };
+    loop {
+        let (status, is_end) = match watch.status().await {
+            Ok(Some(status)) => (HealthStatus::Status(status), false),
+            Ok(None) => (HealthStatus::Closed, true),
+            Err(err) => (HealthStatus::error(&err.into()), true),
+        };
+        if sx.send(status.into()).await.is_err() || is_end {
+            break;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_health_header_start_stop() {
+        // given
+        let mut health = HealthHeader::default();
+
+        // when
+        health.on_key(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE));
+
+        // then
+        assert!(health.dispatch);
+        assert_eq!(health.status, HealthStatus::Checking);
+        assert_eq!(health.method(), HealthMethod::Check);
+
+        // when
+        health.on_key(KeyEvent::new(KeyCode::Char('t'), KeyModifiers::CONTROL));
+        health.stop();
+
+        // then
+        assert_eq!(health.method(), HealthMethod::Watch);
+        assert_eq!(health.status, HealthStatus::None);
+    }
+
+    #[test]
+    fn test_health_status_error() {
+        // given
+        let err = ErrorKind::default_error("status: NotFound\n\ndetails");
+
+        // when
+        let status = HealthStatus::error(&err);
+
+        // then
+        assert_eq!(status, HealthStatus::Error("status: NotFound".to_string()));
+    }
+}
@@ -0,0 +1,89 @@
+use crate::widgets::editor::TextEditor;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use edtui::EditorMode;
+
+/// The per request TLS settings.
+#[derive(Default)]
+pub struct TlsHeader {
+    /// Overrides the TLS server name and the `:authority`.
+    pub(crate) authority: TextEditor,
+    /// Whether the server certificate is not verified.
+    pub(crate) insecure: bool,
+}
+
+impl TlsHeader {
+    pub fn toggle_insecure(&mut self) {
+        self.insecure = !self.insecure;
+    }
+
+    pub fn on_key(&mut self, event: KeyEvent) {
+        let navigation_enabled = self.mode() == EditorMode::Normal;
+        match event.code {
+            KeyCode::Char('t') if event.modifiers == KeyModifiers::CONTROL => {
+                self.toggle_insecure();
+            }
+            KeyCode::Left | KeyCode::Right if navigation_enabled => {
+                self.toggle_insecure();
+            }
+            KeyCode::Char('l') if navigation_enabled && self.authority.is_last_col() => {
+                self.toggle_insecure();
+            }
+            KeyCode::Char('h') if navigation_enabled && self.authority.is_first_col() => {
+                self.toggle_insecure();
+            }
+            _ => self.authority.on_key(event, true),
+        }
+    }
+
+    pub fn mode(&self) -> EditorMode {
+        self.authority.state.mode
+    }
+
+    /// Returns the authority override or `None` if it is empty.
+    pub fn authority(&self) -> Option<String> {
+        Some(self.authority.get_text_raw()).filter(|authority| !authority.is_empty())
+    }
+
+    pub fn set_authority(&mut self, value: &str) {
+        self.authority.set_text_raw(value);
+    }
+
+    pub(super) fn clear(&mut self) {
+        self.authority.clear();
+        self.insecure = false;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tls_header_authority() {
+        // given
+        let mut tls = TlsHeader::default();
+
+        // when
+        let empty = tls.authority();
+        tls.set_authority("example.com");
+
+        // then
+        assert_eq!(empty, None);
+        assert_eq!(tls.authority(), Some(String::from("example.com")));
+    }
+
+    #[test]
+    fn test_tls_header_toggle_with_authority() {
+        // given
+        let mut tls = TlsHeader::default();
+        tls.set_authority("example.com");
+        let right = KeyEvent::new(KeyCode::Right, KeyModifiers::NONE);
+
+        // when
+        tls.on_key(right);
+
+        // then
+        assert!(tls.insecure);
+        assert_eq!(tls.authority(), Some(String::from("example.com")));
+    }
+}
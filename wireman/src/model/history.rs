@@ -1,7 +1,11 @@
-use super::MessagesModel;
+use super::{messages::ResponseModel, MessagesModel};
 use crate::term::Term;
 use config::Config;
-use core::MethodDescriptor;
+use core::{
+    client::{metrics::Metrics, transport::Transport},
+    descriptor::raw::RawFormat,
+    MethodDescriptor,
+};
 use logger::Logger;
 use serde::{Deserialize, Serialize};
 use std::{
@@ -81,7 +85,12 @@ impl HistoryModel {
             return;
         };
 
-        let Ok(message) = messages.request.editor.get_text_json() else {
+        let raw = messages.request.raw;
+        let message = if raw.is_some() {
+            messages.request.editor.get_text_raw()
+        } else if let Ok(message) = messages.request.editor.get_text_json() {
+            message
+        } else {
             Logger::debug("history: failed to parse request");
             return;
         };
@@ -102,25 +111,74 @@ impl HistoryModel {
 
         let address = messages.headers_model.borrow().address();
         let auth_str = messages.headers_model.borrow().auth.value();
-        let auth = Option::from(!auth_str.is_empty()).map(|_| auth_str);
+        let auth = Some(auth_str).filter(|s| !s.is_empty());
         let metadata = messages.headers_model.borrow().meta.as_btree();
+        let timeout_str = messages.headers_model.borrow().timeout.get_text_raw();
+        let timeout = Some(timeout_str).filter(|s| !s.is_empty());
+        let insecure = messages.headers_model.borrow().tls.insecure;
+        let authority = messages.headers_model.borrow().tls.authority();
+        // Only a transport which differs from the config of the address is saved
+        let transport = Some(messages.headers_model.borrow().transport).filter(|transport| {
+            *transport
+                != messages
+                    .request
+                    .core_client
+                    .borrow()
+                    .get_transport(&address)
+        });
         let request = HistoryData {
             message,
             address,
             authentication: auth,
             metadata,
+            timeout,
+            insecure,
+            authority,
+            transport,
+            raw,
+            metrics: messages.response.metadata.metrics,
+            responses: messages.response.messages.clone(),
         };
+        write_history(&path, &request);
+    }
 
-        match serde_json::to_string_pretty(&request) {
-            Ok(data) => {
-                std::fs::write(path, data).unwrap_or_else(|_| {
-                    Logger::debug("history: unable to write file");
-                });
-            }
-            Err(_) => {
-                Logger::debug("history: unable to convert to json");
-            }
+    /// Stores the metrics and the messages of the response with the
+    /// saved request of a save spot. Called when the response of an
+    /// autosaved request arrived, with the spot and method of the time
+    /// the request was sent.
+    pub fn save_response(
+        &self,
+        save_spot: usize,
+        method: &MethodDescriptor,
+        response: &ResponseModel,
+    ) {
+        if self.disabled {
+            return;
+        }
+        let Some(metrics) = response.metadata.metrics else {
+            return;
+        };
+        let Some(path) = self.path(save_spot, method) else {
+            return;
+        };
+        let Some(mut history) = read_history(&path) else {
+            return;
+        };
+        history.metrics = Some(metrics);
+        history.responses = response.messages.clone();
+        write_history(&path, &history);
+    }
+
+    /// Returns the json of the response messages that are saved with
+    /// the request of a save spot.
+    pub fn responses(&self, save_spot: usize, method: &MethodDescriptor) -> Vec<String> {
+        if self.disabled {
+            return Vec::new();
         }
+        self.path(save_spot, method)
+            .and_then(|path| read_history(&path))
+            .map(|history| history.responses)
+            .unwrap_or_default()
     }
 
     /// Loads a request from history.
@@ -133,22 +191,8 @@ impl HistoryModel {
             Logger::debug("history: no method selected");
             return None;
         };
-        let Some(path) = self.path(self.save_spot(), method).clone() else {
-            return None;
-        };
-        if !path.exists() {
-            return None;
-        }
-        let Ok(content) = std::fs::read_to_string(path.clone()) else {
-            Logger::debug(format!("history: failed to read file {path:?}"));
-            return None;
-        };
-        let history: HistoryData = if let Ok(history) = serde_json::from_str(&content) {
-            history
-        } else {
-            Logger::debug("history: failed to parse from str");
-            return None;
-        };
+        let path = self.path(self.save_spot(), method).clone()?;
+        let history = read_history(&path)?;
         history.apply(messages);
         Some(())
     }
@@ -181,12 +225,62 @@ impl HistoryModel {
     }
 }
 
+/// Reads a saved request. Returns `None` if there is none.
+fn read_history(path: &Path) -> Option<HistoryData> {
+    if !path.exists() {
+        return None;
+    }
+    let Ok(content) = std::fs::read_to_string(path) else {
+        Logger::debug(format!("history: failed to read file {path:?}"));
+        return None;
+    };
+    if let Ok(history) = serde_json::from_str(&content) {
+        Some(history)
+    } else {
+        Logger::debug("history: failed to parse from str");
+        None
+    }
+}
+
+/// Writes a request to history.
+fn write_history(path: &Path, history: &HistoryData) {
+    match serde_json::to_string_pretty(history) {
+        Ok(data) => {
+            std::fs::write(path, data).unwrap_or_else(|_| {
+                Logger::debug("history: unable to write file");
+            });
+        }
+        Err(_) => {
+            Logger::debug("history: unable to convert to json");
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize, Eq, PartialEq, Debug)]
 pub struct HistoryData {
     pub message: String,
     pub address: String,
     pub authentication: Option<String>,
     pub metadata: BTreeMap<String, String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout: Option<String>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub insecure: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub authority: Option<String>,
+    /// The transport, None if it is defined by the config of the address.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub transport: Option<Transport>,
+    /// The encoding of a raw payload message, None for json messages.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub raw: Option<RawFormat>,
+    /// The timings and message sizes of the last response.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub metrics: Option<Metrics>,
+    /// The json of the response messages of the last response, served
+    /// by `wireman mock`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub responses: Vec<String>,
 }
 
 impl HistoryData {
@@ -201,6 +295,13 @@ impl HistoryData {
             address,
             authentication,
             metadata,
+            timeout: None,
+            insecure: false,
+            authority: None,
+            transport: None,
+            raw: None,
+            metrics: None,
+            responses: Vec::new(),
         }
     }
 
@@ -216,12 +317,27 @@ impl HistoryData {
         let mut headers_model = messages.headers_model.borrow_mut();
         headers_model.clear();
         headers_model.addr.set_text_raw(&self.address);
+        headers_model.transport = self.transport.unwrap_or_else(|| {
+            messages
+                .request
+                .core_client
+                .borrow()
+                .get_transport(&self.address)
+        });
         if let Some(auth) = &self.authentication {
             headers_model.auth.set_text(auth);
         } else {
             headers_model.auth.set_text("");
         }
         headers_model.meta.set_btree(&self.metadata);
+        headers_model
+            .timeout
+            .set_text_raw(self.timeout.as_deref().unwrap_or_default());
+        headers_model.tls.insecure = self.insecure;
+        if let Some(authority) = &self.authority {
+            headers_model.tls.set_authority(authority);
+        }
+        messages.request.raw = self.raw;
         messages.request.editor.set_text_raw(&self.message);
     }
 }
@@ -230,6 +346,9 @@ impl HistoryData {
 mod tests {
 
     use super::*;
+    use crate::model::{headers::HeadersModel, CoreClient};
+    use core::ProtoDescriptor;
+    use std::{cell::RefCell, rc::Rc};
 
     #[test]
     fn test_history_data_to_json() {
@@ -243,6 +362,13 @@ mod tests {
             address: "Test address".to_string(),
             authentication: Some("Bearer Test".to_string()),
             metadata,
+            timeout: None,
+            insecure: false,
+            authority: None,
+            transport: None,
+            raw: None,
+            metrics: None,
+            responses: Vec::new(),
         };
 
         // when
@@ -261,6 +387,51 @@ mod tests {
         assert_eq!(pretty_json, expected_pretty_json);
     }
 
+    #[test]
+    fn test_history_data_metrics() {
+        // given
+        let mut history_data = HistoryData::new(
+            "{}".to_string(),
+            "http://localhost:50051".to_string(),
+            None,
+            BTreeMap::new(),
+        );
+        history_data.metrics = Some(Metrics {
+            total: std::time::Duration::from_millis(5),
+            request_size: 2,
+            ..Metrics::default()
+        });
+
+        // when
+        let json = history_data.to_json();
+        let parsed: HistoryData = serde_json::from_str(&json).unwrap();
+
+        // then
+        assert!(json.contains(r#""total": 5.0"#));
+        assert_eq!(parsed, history_data);
+    }
+
+    #[test]
+    fn test_history_data_responses() {
+        // given
+        let json = r#"{
+  "message": "{}",
+  "address": "http://localhost:50051",
+  "authentication": null,
+  "metadata": {},
+  "responses": [
+    "{\"message\":\"Hello\"}"
+  ]
+}"#;
+
+        // when
+        let history_data: HistoryData = serde_json::from_str(json).unwrap();
+
+        // then
+        assert_eq!(history_data.responses, vec![r#"{"message":"Hello"}"#]);
+        assert_eq!(history_data.to_json(), json);
+    }
+
     #[test]
     fn test_history_apply() {
         // given
@@ -273,6 +444,13 @@ mod tests {
             address: "Test address".to_string(),
             authentication: Some("Bearer test".to_string()),
             metadata,
+            timeout: Some("5s".to_string()),
+            insecure: true,
+            authority: Some("example.com".to_string()),
+            transport: Some(Transport::GrpcWeb),
+            raw: Some(RawFormat::Hex),
+            metrics: None,
+            responses: Vec::new(),
         };
 
         // when
@@ -282,11 +460,124 @@ mod tests {
         //  then
         let expected_message = r#"Test message"#;
         assert_eq!(messages.request.editor.get_text_raw(), expected_message);
+        assert_eq!(messages.request.raw, Some(RawFormat::Hex));
 
         let expected_address = "Test address";
         assert_eq!(messages.headers_model.borrow().address(), expected_address);
 
         let expected_auth = "Bearer test";
         assert_eq!(messages.headers_model.borrow().auth.value(), expected_auth);
+
+        let expected_timeout = "5s";
+        assert_eq!(
+            messages.headers_model.borrow().timeout.get_text_raw(),
+            expected_timeout
+        );
+
+        assert_eq!(
+            messages.headers_model.borrow().transport,
+            Transport::GrpcWeb
+        );
+        assert!(messages.headers_model.borrow().tls.insecure);
+        let expected_authority = Some("example.com".to_string());
+        assert_eq!(
+            messages.headers_model.borrow().tls.authority(),
+            expected_authority
+        );
+    }
+
+    #[test]
+    fn test_history_apply_without_timeout() {
+        // given
+        let mut messages = MessagesModel::default();
+        messages
+            .headers_model
+            .borrow_mut()
+            .timeout
+            .set_text_raw("5s");
+        let history_data = HistoryData::new(
+            "{}".to_string(),
+            "http://localhost:50051".to_string(),
+            None,
+            BTreeMap::new(),
+        );
+
+        // when
+        history_data.apply(&mut messages);
+
+        // then
+        assert_eq!(messages.headers_model.borrow().timeout.get_text_raw(), "");
+    }
+
+    #[test]
+    fn test_history_apply_transport_of_address() {
+        // given
+        let mut cfg = Config::default();
+        cfg.server.transports.insert(
+            "http://localhost:8080".to_string(),
+            config::config::Transport::GrpcWeb,
+        );
+        let core_client = CoreClient::new(&cfg, ProtoDescriptor::default()).unwrap();
+        let mut messages = MessagesModel::new(
+            Rc::new(RefCell::new(core_client)),
+            Rc::new(RefCell::new(HeadersModel::default())),
+            HistoryModel::default(),
+        );
+        let history_data = HistoryData::new(
+            "{}".to_string(),
+            "http://localhost:8080".to_string(),
+            None,
+            BTreeMap::new(),
+        );
+
+        // when
+        history_data.apply(&mut messages);
+
+        // then
+        assert_eq!(
+            messages.headers_model.borrow().transport,
+            Transport::GrpcWeb
+        );
+    }
+
+    #[test]
+    fn test_history_save_response_to_sent_spot() {
+        // given
+        let base_path =
+            std::env::temp_dir().join(format!("wireman-history-{}", std::process::id()));
+        let history = HistoryModel {
+            base_path: base_path.clone(),
+            save_spot: 1,
+            disabled: false,
+            autosave: true,
+        };
+        let desc =
+            ProtoDescriptor::new(vec!["../wireman-core"], vec!["test_files/test.proto"]).unwrap();
+        let method = desc
+            .get_method_by_name("proto.TestService", "Simple")
+            .unwrap();
+        std::fs::create_dir_all(base_path.join(method.full_name())).unwrap();
+        let path = history.path(2, &method).unwrap();
+        let request = HistoryData::new(
+            "{}".to_string(),
+            "http://localhost:50051".to_string(),
+            None,
+            BTreeMap::new(),
+        );
+        write_history(&path, &request);
+        let mut response = ResponseModel::new();
+        response.metadata.metrics = Some(Metrics::default());
+        response.messages = vec![r#"{"number":1}"#.to_string()];
+
+        // when
+        history.save_response(2, &method, &response);
+
+        // then
+        let saved = read_history(&path).unwrap();
+        let other_spot_exists = history.path(1, &method).unwrap().exists();
+        let _ = std::fs::remove_dir_all(base_path);
+        assert_eq!(saved.metrics, Some(Metrics::default()));
+        assert_eq!(saved.responses, response.messages);
+        assert!(!other_spot_exists);
     }
 }
@@ -1,9 +1,27 @@
 #![allow(clippy::module_name_repetitions)]
-use super::{core_client::CoreClient, headers::HeadersModel, history::HistoryModel};
-use crate::widgets::editor::{pretty_format_json, yank_to_clipboard, ErrorKind, TextEditor};
-use core::{descriptor::RequestMessage, MethodDescriptor};
-use std::{cell::RefCell, collections::HashMap, rc::Rc};
-use tokio::task::JoinHandle;
+use super::{
+    core_client::{ClientCache, CoreClient},
+    headers::HeadersModel,
+    history::HistoryModel,
+};
+use crate::{
+    app::InternalStreamData,
+    widgets::editor::{pretty_format_json, yank_to_clipboard, ErrorKind, TextEditor},
+};
+use core::{
+    client::{
+        connection::ConnectionConfig,
+        health::{health_request, HealthMethod},
+        metrics::Metrics,
+        ResponseStream, SessionSender,
+    },
+    descriptor::{
+        metadata::Metadata, raw::RawFormat, DynamicMessage, RequestMessage, ResponseMessage,
+    },
+    MethodDescriptor,
+};
+use std::{cell::RefCell, collections::HashMap, rc::Rc, time::Duration};
+use tokio::{sync::mpsc::Sender, task::JoinHandle};
 
 /// Map from Method to request/response message
 type MessagesCache = HashMap<String, (String, String)>;
@@ -36,6 +54,16 @@ pub struct MessagesModel {
     /// The task handler of the grpc request. Is None
     /// if no request is dispatched.
     pub handler: Option<JoinHandle<()>>,
+
+    /// The history save spot and the method of the dispatched request.
+    /// Its response is saved there, even if another spot or method is
+    /// selected while the request is pending.
+    pub history_spot: Option<(usize, MethodDescriptor)>,
+
+    /// The sending half of an open bidirectional streaming
+    /// session. Is None if no session is open or if the
+    /// session was half-closed.
+    pub session: Option<SessionSender>,
 }
 
 impl Default for MessagesModel {
@@ -68,6 +96,8 @@ impl MessagesModel {
             history_model,
             dispatch: false,
             handler: None,
+            history_spot: None,
+            session: None,
         }
     }
 
@@ -126,6 +156,7 @@ impl MessagesModel {
         let (req, resp) = self.cache[id].clone();
         self.request.editor.set_text_raw(&req);
         self.response.editor.set_text_raw(&resp);
+        self.response.metadata = ResponseMetadata::default();
     }
 
     /// Returns the request as json string
@@ -149,21 +180,99 @@ impl MessagesModel {
     /// request is done on the next frame.
     pub fn start_request(&mut self) {
         self.dispatch = true;
+        self.response.stream_started = false;
+        self.response.metadata = ResponseMetadata::default();
+        self.response.messages.clear();
         self.response.editor.set_text_raw("Processing...");
         self.response.editor.set_error(None);
     }
 
     /// This method should be called to abort a grpc request.
+    /// Cancels an open streaming session.
     pub fn abort_request(&mut self) {
+        self.session.take();
         if let Some(handler) = self.handler.take() {
             handler.abort();
-            self.response.editor.set_text_raw("User cancelled");
+            if self.response.stream_started {
+                self.response.editor.append_text_raw("User cancelled");
+            } else {
+                self.response.editor.set_text_raw("User cancelled");
+            }
             self.response.editor.set_error(None);
         }
     }
 
+    /// Whether the selected method is bidirectional streaming
+    /// and a session is open for sending messages.
+    pub fn is_session_open(&self) -> bool {
+        self.session.is_some()
+    }
+
+    /// Sends the message of the request editor over the open
+    /// bidirectional streaming session.
+    pub fn send_session_message(&mut self) {
+        let Some(session) = self.session.clone() else {
+            return;
+        };
+        let result = self
+            .collect_request()
+            .and_then(|req| session.send(req).map_err(ErrorKind::from));
+        match result {
+            Ok(()) => {
+                self.response.sent_count += 1;
+                let count = self.response.sent_count;
+                self.response
+                    .editor
+                    .append_text_raw(&format!("Sent message {count}"));
+            }
+            Err(err) => {
+                self.response.editor.append_text_raw(&err.string());
+            }
+        }
+    }
+
+    /// Half-closes an open bidirectional streaming session. No more
+    /// messages can be sent, but the server can still respond.
+    pub fn close_session(&mut self) {
+        if self.session.take().is_some() {
+            self.response.editor.append_text_raw("Sending closed");
+        }
+    }
+
     // Collect the grpc request
     pub fn collect_request(&mut self) -> Result<RequestMessage, ErrorKind> {
+        let text = self.request.editor.get_text_raw();
+        let mut req = self.build_request()?;
+        self.set_request_message(&mut req, &text)?;
+        Ok(req)
+    }
+
+    /// Collect the grpc requests of a client stream. The request editor
+    /// holds either a json array or a sequence of json messages (NDJSON).
+    /// Raw payloads are entered one per line. The headers are built once
+    /// and shared by all messages.
+    pub fn collect_requests(&mut self) -> Result<Vec<RequestMessage>, ErrorKind> {
+        let text = self.request.editor.get_text_raw();
+        let messages = match self.request.raw {
+            Some(_) => split_raw_payloads(&text),
+            None => split_json_messages(&text)?,
+        };
+        if messages.is_empty() {
+            return Err(ErrorKind::default_error("Enter at least one message"));
+        }
+        let req = self.build_request()?;
+        messages
+            .iter()
+            .map(|message| {
+                let mut req = req.clone();
+                self.set_request_message(&mut req, message)?;
+                Ok(req)
+            })
+            .collect()
+    }
+
+    /// Builds a grpc request of the selected method with the headers.
+    fn build_request(&mut self) -> Result<RequestMessage, ErrorKind> {
         let Some(method) = self.selected_method.clone() else {
             let err = ErrorKind::default_error("Select a method!");
             self.response.editor.set_error(Some(err.clone()));
@@ -171,26 +280,73 @@ impl MessagesModel {
             return Err(ErrorKind::default_error("No method selected"));
         };
         let mut req = self.request.core_client.borrow().get_request(&method);
+        self.apply_headers(&mut req)?;
+        Ok(req)
+    }
 
-        // Message
-        if let Err(err) = req
-            .message_mut()
-            .from_json(&self.request.editor.get_text_raw())
-        {
+    /// Sets the message of a request from json, or from a raw payload
+    /// in raw mode.
+    fn set_request_message(
+        &self,
+        req: &mut RequestMessage,
+        message: &str,
+    ) -> Result<(), ErrorKind> {
+        if let Some(format) = self.request.raw {
+            let raw = format.decode(message).map_err(ErrorKind::from)?;
+            req.set_raw(Some(raw.into()));
+        } else if let Err(err) = req.message_mut().from_json(message) {
             return Err(ErrorKind::default_error(err.to_string()));
         }
+        Ok(())
+    }
+
+    /// Builds the `grpc.health.v1` request of the health check on the
+    /// headers page. The timeout of the headers page applies to `Check`
+    /// only.
+    pub fn collect_health_request(&self) -> Result<RequestMessage, ErrorKind> {
+        let (method, service) = {
+            let headers_model = self.headers_model.borrow();
+            (
+                headers_model.health.method(),
+                headers_model.health.service(),
+            )
+        };
+        let mut req = health_request(method, &service)?;
+        self.apply_headers(&mut req)?;
+        // A watch streams until it is stopped, the deadline is not applied
+        if method == HealthMethod::Watch {
+            req.set_timeout(None);
+        }
+        Ok(req)
+    }
 
-        // Metadata
-        let headers_model = self.headers_model.borrow();
+    /// Applies the metadata, the address and the timeout of the headers
+    /// page to a request.
+    fn apply_headers(&self, req: &mut RequestMessage) -> Result<(), ErrorKind> {
+        // Metadata. An invalid header refuses the request.
+        let mut headers_model = self.headers_model.borrow_mut();
+        headers_model.error = None;
         for (key, val) in headers_model.headers_expanded() {
-            if !key.is_empty() {
-                let _ = req.insert_metadata(&key, &val);
+            if key.is_empty() {
+                continue;
+            }
+            if let Err(err) = req.insert_metadata(&key, &val) {
+                headers_model.error = Some(err.to_string());
+                return Err(ErrorKind::default_error(err.to_string()));
             }
         }
 
         // Address
-        req.set_address(&headers_model.address());
-        Ok(req)
+        let address = headers_model.address();
+        let compression = self.request.core_client.borrow().get_compression(&address);
+        req.set_address(&address);
+        req.set_transport(headers_model.transport);
+        req.set_compression(compression);
+
+        // Timeout
+        let timeout = headers_model.timeout().map_err(ErrorKind::default_error)?;
+        req.set_timeout(timeout);
+        Ok(())
     }
 
     pub fn apply_template(&mut self) {
@@ -199,19 +355,65 @@ impl MessagesModel {
         }
     }
 
+    /// Returns the cache of the grpc clients which are reused across calls.
+    pub fn client_cache(&self) -> ClientCache {
+        self.request.core_client.borrow().client_cache()
+    }
+
+    /// Returns the connection settings which are applied to outgoing
+    /// calls. The per request tls settings of the headers page take
+    /// precedence over the config.
+    pub fn connection_config(&self) -> Option<ConnectionConfig> {
+        let connection = self.request.core_client.borrow().get_connection_config();
+        let headers = self.headers_model.borrow();
+        let insecure = headers.tls.insecure;
+        let authority = headers.tls.authority();
+        if !insecure && authority.is_none() {
+            return connection;
+        }
+        let connection = connection.unwrap_or_default();
+        let tls = connection
+            .tls()
+            .clone()
+            .with_insecure(insecure)
+            .with_authority(authority);
+        Some(connection.with_tls(tls))
+    }
+
+    /// Switches the request editor between json, hex and base64 raw
+    /// payloads. The messages of the editor are converted if they are
+    /// valid, unknown fields are lost when converting to json.
+    pub fn toggle_raw_format(&mut self) {
+        let next = match self.request.raw {
+            None => Some(RawFormat::Hex),
+            Some(RawFormat::Hex) => Some(RawFormat::Base64),
+            Some(RawFormat::Base64) => None,
+        };
+        if let Some(method) = &self.selected_method {
+            let text = self.request.editor.get_text_raw();
+            if let Some(text) = convert_payloads(method, &text, self.request.raw, next) {
+                self.request.editor.set_text_raw(&text);
+            }
+        }
+        self.request.raw = next;
+        self.request.editor.set_error(None);
+    }
+
     /// Yanks the request message in grpcurl format
     pub fn yank_grpcurl(&mut self) {
         if let Some(method) = &self.selected_method {
             let address = self.headers_model.borrow().address();
             let message = self.request.editor.get_text_raw();
             let header = self.headers_model.borrow().headers();
+            let connection = self.connection_config();
 
-            if let Ok(text) = self
-                .request
-                .core_client
-                .borrow()
-                .get_grpcurl(&message, method, &header, &address)
-            {
+            if let Ok(text) = self.request.core_client.borrow().get_grpcurl(
+                &message,
+                method,
+                &header,
+                &address,
+                connection.as_ref().map(ConnectionConfig::tls),
+            ) {
                 yank_to_clipboard(&text);
             }
         }
@@ -219,27 +421,130 @@ impl MessagesModel {
 }
 
 /// Make a grpc call and set response or error.
-pub async fn do_request(req: RequestMessage) -> RequestResult {
-    let resp = CoreClient::call_unary_async(&req).await;
-
-    match resp {
-        Ok(resp) => {
-            if let Ok(json) = resp.message.to_json() {
-                let formatted_json = try_pretty_format_json(&json);
-                RequestResult::data(formatted_json)
-            } else {
-                let err = ErrorKind::format_error("failed to parse json".to_string());
-                RequestResult::error(err)
+pub async fn do_request(
+    req: RequestMessage,
+    connection: Option<ConnectionConfig>,
+    clients: ClientCache,
+    raw: Option<RawFormat>,
+) -> RequestResult {
+    let resp = CoreClient::call_unary_async(&req, connection, &clients).await;
+    RequestResult::from_response(resp, raw)
+}
+
+/// Make a client streaming grpc call and set response or error.
+pub async fn do_client_streaming_request(
+    reqs: Vec<RequestMessage>,
+    connection: Option<ConnectionConfig>,
+    clients: ClientCache,
+    raw: Option<RawFormat>,
+) -> RequestResult {
+    let resp = CoreClient::call_client_streaming_async(reqs, connection, &clients).await;
+    RequestResult::from_response(resp, raw)
+}
+
+/// Make a server streaming grpc call and forward each received
+/// message as well as the final status of the stream.
+pub async fn do_server_streaming_request(
+    req: RequestMessage,
+    connection: Option<ConnectionConfig>,
+    clients: ClientCache,
+    raw: Option<RawFormat>,
+    sx: Sender<InternalStreamData>,
+) {
+    match CoreClient::call_server_streaming_async(&req, connection, &clients).await {
+        Ok(stream) => forward_stream(stream, raw, sx).await,
+        Err(err) => {
+            let _ = sx.send(StreamResult::end(Some(err)).into()).await;
+        }
+    };
+}
+
+/// Open a bidirectional streaming session and forward the sending half
+/// of the session as well as each received message and the final status.
+pub async fn do_bidi_streaming_request(
+    req: RequestMessage,
+    connection: Option<ConnectionConfig>,
+    clients: ClientCache,
+    raw: Option<RawFormat>,
+    sx: Sender<InternalStreamData>,
+) {
+    match CoreClient::call_bidi_streaming_async(&req, connection, &clients).await {
+        Ok((sender, response)) => {
+            if sx.send(SessionEvent::Opened(sender).into()).await.is_err() {
+                return;
             }
+            match response.await {
+                Ok(stream) => forward_stream(stream, raw, sx).await,
+                Err(err) => {
+                    let _ = sx.send(StreamResult::end(Some(err)).into()).await;
+                }
+            }
+        }
+        Err(err) => {
+            let _ = sx.send(StreamResult::end(Some(err)).into()).await;
+        }
+    };
+}
+
+/// Forwards the headers and each message of a response stream
+/// until the stream ends. The trailers are forwarded before the end.
+async fn forward_stream(
+    mut stream: ResponseStream,
+    raw: Option<RawFormat>,
+    sx: Sender<InternalStreamData>,
+) {
+    let headers = ResponseMetadata::from_stream(&stream);
+    if sx
+        .send(StreamResult::Metadata(headers).into())
+        .await
+        .is_err()
+    {
+        return;
+    }
+    loop {
+        let result = match stream.message().await {
+            Ok(Some(resp)) => match response_text(&resp, raw) {
+                Ok(text) => {
+                    let metrics = ResponseMetadata::metrics(*resp.metrics());
+                    let _ = sx.send(StreamResult::Metadata(metrics).into()).await;
+                    let json = resp.message.to_json().ok();
+                    StreamResult::message(text, json)
+                }
+                Err(err) => StreamResult::end(Some(err)),
+            },
+            Ok(None) => {
+                if let Ok(trailers) = stream.trailers().await {
+                    let trailers = ResponseMetadata {
+                        metrics: Some(*stream.metrics()),
+                        ..ResponseMetadata::trailers(&trailers)
+                    };
+                    let _ = sx.send(StreamResult::Metadata(trailers).into()).await;
+                }
+                StreamResult::end(None)
+            }
+            Err(err) => {
+                // The headers were forwarded when the stream opened
+                let err = ErrorKind {
+                    headers: Vec::new(),
+                    ..err.into()
+                };
+                StreamResult::end(Some(err))
+            }
+        };
+        let is_end = result.is_end();
+        if sx.send(result.into()).await.is_err() || is_end {
+            break;
         }
-        Err(err) => RequestResult::error(err),
     }
 }
 
 #[derive(Default)]
 pub struct RequestResult {
     data: Option<String>,
+    /// The json of the response message.
+    json: Option<String>,
     error: Option<ErrorKind>,
+    metadata: ResponseMetadata,
 }
 unsafe impl Send for RequestResult {}
 
@@ -247,16 +552,38 @@ impl RequestResult {
     pub fn data(data: String) -> Self {
         Self {
             data: Some(data),
-            error: None,
+            ..Default::default()
         }
     }
     pub fn error(error: ErrorKind) -> Self {
         Self {
-            data: None,
             error: Some(error),
+            ..Default::default()
         }
     }
-    pub fn set(&self, editor: &mut TextEditor) {
+
+    /// Converts the response of a grpc call into a result. In raw mode
+    /// the raw bytes are shown below the decoded message.
+    pub fn from_response(resp: Result<ResponseMessage, ErrorKind>, raw: Option<RawFormat>) -> Self {
+        match resp {
+            Ok(resp) => {
+                let mut result = match response_text(&resp, raw) {
+                    Ok(text) => Self::data(text),
+                    Err(err) => Self::error(err),
+                };
+                result.json = resp.message.to_json().ok();
+                result.metadata = ResponseMetadata::from_response(&resp);
+                result
+            }
+            Err(err) => Self {
+                metadata: ResponseMetadata::from_error(&err),
+                ..Self::error(err)
+            },
+        }
+    }
+
+    pub fn set(&self, response: &mut ResponseModel) {
+        let editor = &mut response.editor;
         if let Some(text) = &self.data {
             editor.set_error(None);
             editor.set_text_raw(text);
@@ -265,19 +592,249 @@ impl RequestResult {
             editor.set_error(Some(error.clone()));
             editor.set_text_raw(&error.string());
         }
+        response.metadata = self.metadata.clone();
+        response.messages = self.json.iter().cloned().collect();
+    }
+}
+
+/// The headers and trailers of a response as key value strings.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct ResponseMetadata {
+    pub headers: Vec<(String, String)>,
+    pub trailers: Vec<(String, String)>,
+    /// The compression of the response messages, `None` if the
+    /// messages are not compressed.
+    pub encoding: Option<String>,
+    /// The number of attempts of the call, zero if unknown.
+    pub attempts: u32,
+    /// The timings and message sizes of the call, if known.
+    pub metrics: Option<Metrics>,
+}
+
+impl ResponseMetadata {
+    /// Returns the headers and trailers of a response.
+    pub fn from_response(resp: &ResponseMessage) -> Self {
+        Self {
+            headers: resp.headers().to_pairs(),
+            trailers: resp.trailers().to_pairs(),
+            encoding: resp.headers().encoding().map(str::to_string),
+            attempts: resp.attempts(),
+            metrics: Some(*resp.metrics()),
+        }
+    }
+
+    /// Returns the headers and trailers sent with a failed call.
+    pub fn from_error(err: &ErrorKind) -> Self {
+        Self {
+            headers: err.headers.clone(),
+            trailers: err.trailers.clone(),
+            ..Self::default()
+        }
+    }
+
+    /// Returns metadata which holds only headers.
+    pub fn headers(headers: &Metadata) -> Self {
+        Self {
+            headers: headers.to_pairs(),
+            trailers: Vec::new(),
+            encoding: headers.encoding().map(str::to_string),
+            attempts: 0,
+            metrics: None,
+        }
+    }
+
+    /// Returns the headers, the number of attempts and the metrics
+    /// of a stream.
+    pub fn from_stream(stream: &ResponseStream) -> Self {
+        Self {
+            attempts: stream.attempts(),
+            metrics: Some(*stream.metrics()),
+            ..Self::headers(stream.headers())
+        }
+    }
+
+    /// Returns metadata which holds only trailers.
+    pub fn trailers(trailers: &Metadata) -> Self {
+        Self {
+            headers: Vec::new(),
+            trailers: trailers.to_pairs(),
+            encoding: None,
+            attempts: 0,
+            metrics: None,
+        }
+    }
+
+    /// Returns metadata which holds only metrics.
+    pub fn metrics(metrics: Metrics) -> Self {
+        Self {
+            metrics: Some(metrics),
+            ..Self::default()
+        }
+    }
+
+    /// Merges the headers and trailers of other into self.
+    pub fn merge(&mut self, other: &Self) {
+        self.headers.extend(other.headers.iter().cloned());
+        self.trailers.extend(other.trailers.iter().cloned());
+        if other.encoding.is_some() {
+            self.encoding.clone_from(&other.encoding);
+        }
+        self.attempts = self.attempts.max(other.attempts);
+        if other.metrics.is_some() {
+            self.metrics = other.metrics;
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.headers.is_empty() && self.trailers.is_empty()
+    }
+
+    /// Returns the metrics as status line, e.g. `Connect 1.2 ms | TTFB
+    /// 3.4 ms | Total 5.0 ms | Sent 12 B | Received 1.5 KiB`. The connect
+    /// time is omitted if an open connection was reused.
+    pub fn metrics_line(&self) -> Option<String> {
+        let metrics = self.metrics?;
+        let mut parts = Vec::new();
+        if let Some(connect) = metrics.connect {
+            parts.push(format!("Connect {}", format_duration(connect)));
+        }
+        if let Some(first_byte) = metrics.first_byte {
+            parts.push(format!("TTFB {}", format_duration(first_byte)));
+        }
+        parts.push(format!("Total {}", format_duration(metrics.total)));
+        parts.push(format!("Sent {}", format_size(metrics.request_size)));
+        parts.push(format!("Received {}", format_size(metrics.response_size)));
+        Some(parts.join(" | "))
+    }
+}
+
+/// Formats a duration in milliseconds, or in seconds from one second.
+fn format_duration(duration: Duration) -> String {
+    let millis = duration.as_secs_f64() * 1000.0;
+    if millis < 1000.0 {
+        format!("{millis:.1} ms")
+    } else {
+        format!("{:.2} s", millis / 1000.0)
+    }
+}
+
+/// Formats a size in bytes, KiB or MiB.
+#[allow(clippy::cast_precision_loss)]
+fn format_size(bytes: usize) -> String {
+    const KIB: usize = 1024;
+    if bytes < KIB {
+        format!("{bytes} B")
+    } else if bytes < KIB * KIB {
+        format!("{:.1} KiB", bytes as f64 / KIB as f64)
+    } else {
+        format!("{:.1} MiB", bytes as f64 / (KIB * KIB) as f64)
+    }
+}
+
+/// A single event of a streaming grpc call. Is either a
+/// received message or the final status of the stream.
+pub enum StreamResult {
+    /// The headers or trailers received from the server
+    Metadata(ResponseMetadata),
+    /// A message received from the server, formatted for the
+    /// editor, and its json. The json is `None` if the message
+    /// cannot be converted, it is not saved with the history then.
+    Message { text: String, json: Option<String> },
+    /// The stream was closed. Holds an error if the
+    /// stream did not finish successfully.
+    End(Option<ErrorKind>),
+}
+
+impl StreamResult {
+    pub fn message(text: String, json: Option<String>) -> Self {
+        Self::Message { text, json }
+    }
+
+    pub fn end(error: Option<ErrorKind>) -> Self {
+        Self::End(error)
+    }
+
+    /// Whether this is the final event of the stream
+    pub fn is_end(&self) -> bool {
+        matches!(self, Self::End(_))
+    }
+
+    /// Appends the streamed message or the final status
+    /// to the response.
+    pub fn set(&self, response: &mut ResponseModel) {
+        // Clear the processing indicator on the first event
+        if !response.stream_started {
+            response.start_stream();
+        }
+        match self {
+            Self::Metadata(metadata) => {
+                response.metadata.merge(metadata);
+            }
+            Self::Message { text, json } => {
+                response.stream_count += 1;
+                let count = response.stream_count;
+                response
+                    .editor
+                    .append_text_raw(&format!("Message {count}:\n{text}"));
+                response.messages.extend(json.clone());
+            }
+            Self::End(None) => {
+                let count = response.stream_count;
+                response
+                    .editor
+                    .append_text_raw(&format!("Stream finished: {count} message(s)"));
+                response.editor.set_error(None);
+            }
+            Self::End(Some(error)) => {
+                response
+                    .metadata
+                    .merge(&ResponseMetadata::from_error(error));
+                let count = response.stream_count;
+                let msg = error.string();
+                response
+                    .editor
+                    .append_text_raw(&format!("Stream failed after {count} message(s): {msg}"));
+                response.editor.set_error(Some(error.clone()));
+            }
+        }
+    }
+}
+
+/// An event of a bidirectional streaming session.
+pub enum SessionEvent {
+    /// The session was opened. Holds the sending half of the session.
+    Opened(SessionSender),
+}
+
+impl SessionEvent {
+    /// Stores the sending half of an opened session and logs
+    /// the first message, which is sent on opening.
+    pub fn set(&self, messages: &mut MessagesModel) {
+        match self {
+            Self::Opened(sender) => {
+                messages.session = Some(sender.clone());
+                messages.response.start_stream();
+                messages.response.sent_count = 1;
+                messages.response.editor.append_text_raw("Sent message 1");
+            }
+        }
     }
 }
 
 #[derive(Clone)]
 pub struct RequestModel {
     /// The core client retrieves default proto message and making grpc calls.
-    core_client: Rc<RefCell<CoreClient>>,
+    pub(crate) core_client: Rc<RefCell<CoreClient>>,
 
     /// The currently active editor
     pub editor: TextEditor,
 
     /// The metadata
     pub metadata: String,
+
+    /// The encoding of the raw payloads in the editor. Is None if the
+    /// editor holds json messages.
+    pub raw: Option<RawFormat>,
 }
 
 impl RequestModel {
@@ -288,19 +845,24 @@ impl RequestModel {
             core_client,
             editor: TextEditor::new(),
             metadata: String::new(),
+            raw: None,
         }
     }
 
-    /// Loads a new request message template into the editor.
+    /// Loads a new request message template into the editor. Client
+    /// streaming methods get a json array holding a single message.
     pub fn load_template(&mut self, method: &MethodDescriptor) {
-        let req = self
+        let mut req = self
             .core_client
             .borrow_mut()
             .get_request(method)
             .message()
             .to_json()
-            .map_or("{}".to_string(), |r| try_pretty_format_json(&r));
-        self.editor.set_text_raw(&req);
+            .unwrap_or_else(|_| "{}".to_string());
+        if method.is_client_streaming() {
+            req = format!("[{req}]");
+        }
+        self.editor.set_text_raw(&try_pretty_format_json(&req));
     }
 
     pub fn set_text(&mut self, text: &str) {
@@ -316,6 +878,22 @@ impl RequestModel {
 pub struct ResponseModel {
     // The response text field
     pub editor: TextEditor,
+
+    /// The number of messages received from a server stream
+    pub stream_count: usize,
+
+    /// The number of messages sent in a bidirectional session
+    pub sent_count: usize,
+
+    /// Whether the editor holds the log of a stream
+    pub stream_started: bool,
+
+    /// The headers and trailers of the last response
+    pub metadata: ResponseMetadata,
+
+    /// The json of the messages of the last response. They are saved
+    /// with the history and served by the mock server.
+    pub messages: Vec<String>,
 }
 
 impl ResponseModel {
@@ -323,9 +901,25 @@ impl ResponseModel {
     pub fn new() -> Self {
         Self {
             editor: TextEditor::new(),
+            stream_count: 0,
+            sent_count: 0,
+            stream_started: false,
+            metadata: ResponseMetadata::default(),
+            messages: Vec::new(),
         }
     }
 
+    /// Clears the response and resets the stream counters to
+    /// start a new stream log.
+    pub fn start_stream(&mut self) {
+        self.editor.clear();
+        self.editor.set_error(None);
+        self.stream_count = 0;
+        self.sent_count = 0;
+        self.stream_started = true;
+        self.messages.clear();
+    }
+
     /// Clears the response
     pub fn clear(&mut self) {
         self.editor = TextEditor::new();
@@ -340,8 +934,301 @@ impl ResponseModel {
     }
 }
 
+/// Formats the message of a response as json. In raw mode, the raw
+/// bytes are appended in the given format.
+fn response_text(resp: &ResponseMessage, raw: Option<RawFormat>) -> Result<String, ErrorKind> {
+    let json = resp
+        .message
+        .to_json()
+        .map_err(|_| ErrorKind::format_error("failed to parse json".to_string()))?;
+    let mut text = try_pretty_format_json(&json);
+    if let Some(format) = raw {
+        text.push_str(&format!(
+            "\n\nRaw ({format}):\n{}",
+            format.encode(resp.raw())
+        ));
+    }
+    Ok(text)
+}
+
+/// Splits the text of the request editor into raw payloads, one per line.
+fn split_raw_payloads(input: &str) -> Vec<String> {
+    input
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(String::from)
+        .collect()
+}
+
+/// Converts the messages of the request editor from one encoding into
+/// another. Json is used if the encoding is None. Returns None if the
+/// messages are invalid.
+fn convert_payloads(
+    method: &MethodDescriptor,
+    text: &str,
+    from: Option<RawFormat>,
+    to: Option<RawFormat>,
+) -> Option<String> {
+    let payloads = match from {
+        Some(format) => split_raw_payloads(text)
+            .iter()
+            .map(|payload| format.decode(payload).ok())
+            .collect::<Option<Vec<_>>>()?,
+        None => split_json_messages(text)
+            .ok()?
+            .iter()
+            .map(|json| {
+                let mut message = DynamicMessage::new(method.input());
+                message.from_json(json).ok()?;
+                Some(message.to_bytes())
+            })
+            .collect::<Option<Vec<_>>>()?,
+    };
+    if let Some(format) = to {
+        let lines: Vec<String> = payloads.iter().map(|bytes| format.encode(bytes)).collect();
+        return Some(lines.join("\n"));
+    }
+    let messages = payloads
+        .iter()
+        .map(|bytes| {
+            let mut message = DynamicMessage::new(method.input());
+            message.from_bytes(bytes).ok()?;
+            message.to_json().ok()
+        })
+        .collect::<Option<Vec<_>>>()?;
+    let json = if method.is_client_streaming() {
+        format!("[{}]", messages.join(","))
+    } else {
+        messages.into_iter().next()?
+    };
+    Some(try_pretty_format_json(&json))
+}
+
+/// Splits the text of the request editor into json messages. Accepts
+/// a json array of messages or a sequence of json messages such as NDJSON.
+fn split_json_messages(input: &str) -> Result<Vec<String>, ErrorKind> {
+    if input.trim_start().starts_with('[') {
+        let values: Vec<serde_json::Value> = serde_json::from_str(input)?;
+        return Ok(values.iter().map(ToString::to_string).collect());
+    }
+    serde_json::Deserializer::from_str(input)
+        .into_iter::<serde_json::Value>()
+        .map(|value| Ok(value?.to_string()))
+        .collect()
+}
+
 /// Convenienve method to retty format a json string and just return
 /// the input if formatting fails.
 fn try_pretty_format_json(input: &str) -> String {
     pretty_format_json(input).unwrap_or_else(|_| input.to_string())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::ProtoDescriptor;
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn test_stream_result_set() {
+        // given
+        let mut response = ResponseModel::new();
+        response.set_text("Processing...");
+
+        // when
+        StreamResult::message("{}".to_string(), Some("{}".to_string())).set(&mut response);
+        StreamResult::message("{}".to_string(), None).set(&mut response);
+        StreamResult::end(None).set(&mut response);
+
+        // then
+        let expected = "Message 1:\n{}\nMessage 2:\n{}\nStream finished: 2 message(s)";
+        assert_eq!(response.editor.get_text_raw(), expected);
+        assert_eq!(response.stream_count, 2);
+        assert_eq!(response.messages, vec!["{}".to_string()]);
+    }
+
+    #[test]
+    fn test_stream_result_set_metadata() {
+        // given
+        let mut response = ResponseModel::new();
+        let headers = ResponseMetadata {
+            headers: vec![("x-request-id".to_string(), "42".to_string())],
+            trailers: Vec::new(),
+            encoding: Some("gzip".to_string()),
+            attempts: 2,
+            metrics: None,
+        };
+        let trailers = ResponseMetadata {
+            headers: Vec::new(),
+            trailers: vec![("server-timing".to_string(), "10ms".to_string())],
+            encoding: None,
+            attempts: 0,
+            metrics: Some(Metrics {
+                response_size: 12,
+                ..Metrics::default()
+            }),
+        };
+
+        // when
+        StreamResult::Metadata(headers).set(&mut response);
+        StreamResult::message("{}".to_string(), Some("{}".to_string())).set(&mut response);
+        StreamResult::Metadata(trailers).set(&mut response);
+
+        // then
+        assert_eq!(
+            response.metadata.headers,
+            vec![("x-request-id".to_string(), "42".to_string())]
+        );
+        assert_eq!(
+            response.metadata.trailers,
+            vec![("server-timing".to_string(), "10ms".to_string())]
+        );
+        assert_eq!(response.metadata.encoding, Some("gzip".to_string()));
+        assert_eq!(response.metadata.attempts, 2);
+        assert_eq!(response.metadata.metrics.unwrap().response_size, 12);
+    }
+
+    #[test]
+    fn test_metrics_line() {
+        // given
+        let metadata = ResponseMetadata::metrics(Metrics {
+            connect: None,
+            first_byte: Some(Duration::from_micros(3_400)),
+            total: Duration::from_millis(1_250),
+            request_size: 12,
+            response_size: 1_536,
+        });
+
+        // when
+        let line = metadata.metrics_line();
+
+        // then
+        assert_eq!(
+            line,
+            Some("TTFB 3.4 ms | Total 1.25 s | Sent 12 B | Received 1.5 KiB".to_string())
+        );
+        assert_eq!(ResponseMetadata::default().metrics_line(), None);
+    }
+
+    #[test]
+    fn test_split_json_messages() {
+        // given
+        let array = "[{\"number\": 1}, {\"number\": 2}]";
+        let ndjson = "{\"number\": 1}\n{\"number\": 2}\n";
+        let expected = vec!["{\"number\":1}".to_string(), "{\"number\":2}".to_string()];
+
+        // then
+        assert_eq!(split_json_messages(array).unwrap(), expected);
+        assert_eq!(split_json_messages(ndjson).unwrap(), expected);
+        assert!(split_json_messages("{\"number\": 1").is_err());
+    }
+
+    #[test]
+    fn test_convert_payloads() {
+        // given
+        let desc =
+            ProtoDescriptor::new(vec!["../wireman-core"], vec!["test_files/test.proto"]).unwrap();
+        let method = desc
+            .get_method_by_name("proto.TestService", "Simple")
+            .unwrap();
+        let json = "{\"number\": 5}";
+
+        // when
+        let hex = convert_payloads(&method, json, None, Some(RawFormat::Hex)).unwrap();
+        let base64 =
+            convert_payloads(&method, &hex, Some(RawFormat::Hex), Some(RawFormat::Base64)).unwrap();
+        let back = convert_payloads(&method, &base64, Some(RawFormat::Base64), None).unwrap();
+
+        // then
+        assert_eq!(hex, "0805");
+        assert_eq!(base64, "CAU=");
+        assert_eq!(back, try_pretty_format_json("{\"number\":5}"));
+        assert!(convert_payloads(&method, "zz", Some(RawFormat::Hex), None).is_none());
+    }
+
+    #[test]
+    fn test_collect_requests() {
+        // given
+        let desc =
+            ProtoDescriptor::new(vec!["../wireman-core"], vec!["test_files/test.proto"]).unwrap();
+        let method = desc
+            .get_method_by_name("proto.TestService", "Simple")
+            .unwrap();
+        let mut model = MessagesModel {
+            selected_method: Some(method),
+            ..MessagesModel::default()
+        };
+        model
+            .request
+            .editor
+            .set_text_raw("{\"number\": 1}\n{\"number\": 2}");
+        let metadata = BTreeMap::from([("key".to_string(), "value".to_string())]);
+        model.headers_model.borrow_mut().meta.set_btree(&metadata);
+
+        // when
+        let reqs = model.collect_requests().unwrap();
+
+        // then
+        assert_eq!(reqs.len(), 2);
+        for (req, number) in reqs.iter().zip(["1", "2"]) {
+            assert!(req.to_json().unwrap().contains(number));
+            let metadata = req.metadata().as_ref().unwrap();
+            assert_eq!(metadata.get("key").unwrap(), "value");
+        }
+    }
+
+    #[test]
+    fn test_collect_health_request_watch_without_deadline() {
+        // given
+        let model = MessagesModel::default();
+        model.headers_model.borrow_mut().timeout.set_text_raw("5s");
+
+        // when
+        let check = model.collect_health_request().unwrap();
+        model.headers_model.borrow_mut().health.toggle_watch();
+        let watch = model.collect_health_request().unwrap();
+
+        // then
+        assert_eq!(check.timeout(), Some(Duration::from_secs(5)));
+        assert_eq!(watch.timeout(), None);
+    }
+
+    #[test]
+    fn test_stream_result_set_error() {
+        // given
+        let mut response = ResponseModel::new();
+        let error = ErrorKind::default_error("unavailable");
+
+        // when
+        StreamResult::end(Some(error)).set(&mut response);
+
+        // then
+        let expected = "Stream failed after 0 message(s): Error: unavailable";
+        assert_eq!(response.editor.get_text_raw(), expected);
+        assert!(response.editor.get_error().is_some());
+    }
+
+    #[test]
+    fn test_request_result_error_metadata() {
+        // given
+        let mut response = ResponseModel::new();
+        let error = ErrorKind {
+            headers: vec![("x-request-id".to_string(), "42".to_string())],
+            trailers: vec![("server-timing".to_string(), "10ms".to_string())],
+            ..ErrorKind::default_error("not found")
+        };
+
+        // when
+        RequestResult::from_response(Err(error), None).set(&mut response);
+
+        // then
+        assert_eq!(
+            response.metadata.headers,
+            vec![("x-request-id".to_string(), "42".to_string())]
+        );
+        assert_eq!(
+            response.metadata.trailers,
+            vec![("server-timing".to_string(), "10ms".to_string())]
+        );
+    }
+}
@@ -1,9 +1,10 @@
 use super::root::layout;
 use crate::{
-    model::headers::{AuthSelection, HeadersModel, HeadersSelection},
+    model::headers::{AuthSelection, HeadersModel, HeadersSelection, HealthStatus},
     widgets::editor::{view_single_selected, view_single_unselected},
     widgets::kv::KV,
 };
+use core::client::{health::ServingStatus, transport::Transport};
 use edtui::{EditorState, StatusLine};
 use ratatui::{
     prelude::*,
@@ -29,6 +30,34 @@ impl<'a> HeadersPage<'a> {
                 vec![
                     ("q", "Quit"),
                     ("Esc", "Unselect"),
+                    ("^t", "Transport"),
+                    ("↑/k", "Up"),
+                    ("↓/j", "Down"),
+                ]
+            }
+            HeadersSelection::Timeout => {
+                vec![
+                    ("q", "Quit"),
+                    ("Esc", "Unselect"),
+                    ("↑/k", "Up"),
+                    ("↓/j", "Down"),
+                ]
+            }
+            HeadersSelection::Tls => {
+                vec![
+                    ("q", "Quit"),
+                    ("Esc", "Unselect"),
+                    ("^t", "Toggle Verify"),
+                    ("↑/k", "Up"),
+                    ("↓/j", "Down"),
+                ]
+            }
+            HeadersSelection::Health => {
+                vec![
+                    ("q", "Quit"),
+                    ("Esc", "Unselect"),
+                    ("Enter", "Check/Stop"),
+                    ("^t", "Check/Watch"),
                     ("↑/k", "Up"),
                     ("↓/j", "Down"),
                 ]
@@ -69,28 +98,79 @@ impl<'a> HeadersPage<'a> {
 impl Widget for HeadersPage<'_> {
     fn render(self, area: Rect, buf: &mut ratatui::prelude::Buffer) {
         // let area = layout(area, Direction::Vertical, &[1, 4, 1, 5, 1, 0, 1]);
-        let [addr_title, addr_content, _, auth_title, auth_content, _, meta_title, meta_content, status] =
-            layout(area, Direction::Vertical, &[1, 3, 1, 1, 4, 1, 1, 0, 1]);
+        let [addr_title, addr_content, _, timeout_title, timeout_content, _, tls_title, tls_content, _, health_title, health_content, health_status, _, auth_title, auth_content, _, meta_title, meta_content, status] =
+            layout(
+                area,
+                Direction::Vertical,
+                &[1, 4, 1, 1, 3, 1, 1, 4, 1, 1, 4, 1, 1, 1, 4, 1, 1, 0, 1],
+            );
+        // The error of an invalid header is shown below the headers
+        let [meta_content, error] = if self.model.error.is_some() {
+            layout(meta_content, Direction::Vertical, &[0, 1])
+        } else {
+            [meta_content, Rect::default()]
+        };
 
         // Address
         ListElements::VDivider(String::from(" Address ")).render(addr_title, buf);
-        Address {
+        TabbedEditor {
+            tags: TRANSPORT_TAGS.to_vec(),
             state: self.model.addr.state.clone(),
             title: String::new(),
             selected: self.model.selected == HeadersSelection::Addr,
+            selected_tag: match self.model.transport {
+                Transport::Grpc => 0,
+                Transport::GrpcWeb => 1,
+                Transport::Connect => 2,
+                Transport::ConnectJson => 3,
+            },
         }
         .render(addr_content, buf);
 
+        // Timeout
+        ListElements::VDivider(String::from(" Timeout ")).render(timeout_title, buf);
+        Address {
+            state: self.model.timeout.state.clone(),
+            title: String::new(),
+            selected: self.model.selected == HeadersSelection::Timeout,
+        }
+        .render(timeout_content, buf);
+
+        // TLS
+        ListElements::VDivider(String::from(" TLS ")).render(tls_title, buf);
+        TabbedEditor {
+            tags: vec![" Verify ", " Insecure "],
+            state: self.model.tls.authority.state.clone(),
+            title: String::from("Authority"),
+            selected: self.model.selected == HeadersSelection::Tls,
+            selected_tag: usize::from(self.model.tls.insecure),
+        }
+        .render(tls_content, buf);
+
+        // Health
+        ListElements::VDivider(String::from(" Health ")).render(health_title, buf);
+        TabbedEditor {
+            tags: vec![" Check ", " Watch "],
+            state: self.model.health.service.state.clone(),
+            title: String::from("Service"),
+            selected: self.model.selected == HeadersSelection::Health,
+            selected_tag: usize::from(self.model.health.watch),
+        }
+        .render(health_content, buf);
+        health_indicator(&self.model.health.status).render(health_status, buf);
+
         // Authentication
         ListElements::VDivider(String::from(" Authentication ")).render(auth_title, buf);
         let body = match self.model.auth.selected {
-            AuthSelection::Bearer => Authentication {
+            AuthSelection::Bearer => TabbedEditor {
+                tags: AUTH_TAGS.to_vec(),
                 state: self.model.auth.bearer.state.clone(),
                 title: String::new(),
                 selected: self.model.selected == HeadersSelection::Auth,
                 selected_tag: 0,
             },
-            AuthSelection::Basic => Authentication {
+            AuthSelection::Basic => TabbedEditor {
+                tags: AUTH_TAGS.to_vec(),
                 state: self.model.auth.basic.state.clone(),
                 title: String::new(),
                 selected: self.model.selected == HeadersSelection::Auth,
@@ -112,9 +192,9 @@ impl Widget for HeadersPage<'_> {
                         key: x.0.state.clone(),
                         val: x.1.state.clone(),
                         key_selected: (self.model.selected == HeadersSelection::Meta)
-                            && index.map_or(false, |x| x.row == i && x.col == 0),
+                            && index.is_some_and(|x| x.row == i && x.col == 0),
                         val_selected: (self.model.selected == HeadersSelection::Meta)
-                            && index.map_or(false, |x| x.row == i && x.col == 1),
+                            && index.is_some_and(|x| x.row == i && x.col == 1),
                     })
                     .collect(),
                 selected_row: index.map(|x| x.row),
@@ -122,6 +202,11 @@ impl Widget for HeadersPage<'_> {
             .render(meta_content, buf);
         }
 
+        // Invalid header
+        if let Some(err) = &self.model.error {
+            Line::styled(format!(" {err}"), THEME.error).render(error, buf);
+        }
+
         // Show a combined status line for all editors
         StatusLine::default()
             .style_text(THEME.status_line.0)
@@ -131,6 +216,25 @@ impl Widget for HeadersPage<'_> {
     }
 }
 
+/// Returns the colored indicator of the result of a health check.
+fn health_indicator(status: &HealthStatus) -> Line<'static> {
+    let (text, style) = match status {
+        HealthStatus::None => (String::from("○ Press Enter to check"), THEME.content),
+        HealthStatus::Checking => (String::from("○ Checking..."), THEME.content),
+        HealthStatus::Status(status) => {
+            let style = match status {
+                ServingStatus::Serving => THEME.success,
+                ServingStatus::NotServing => THEME.error,
+                ServingStatus::Unknown | ServingStatus::ServiceUnknown => THEME.warning,
+            };
+            (format!("● {status}"), style)
+        }
+        HealthStatus::Error(err) => (format!("● {err}"), THEME.error),
+        HealthStatus::Closed => (String::from("● Watch closed"), THEME.warning),
+    };
+    Line::styled(format!(" {text}"), style)
+}
+
 #[allow(clippy::large_enum_variant)]
 enum ListElements {
     VSpace(usize),
@@ -172,20 +276,25 @@ impl Widget for Address {
     }
 }
 
+const AUTH_TAGS: [&str; 2] = [" Bearer ", " Basic "];
+
+const TRANSPORT_TAGS: [&str; 4] = [" gRPC ", " gRPC-Web ", " Connect ", " Connect (JSON) "];
+
+/// A single line editor with a row of tags above.
 #[derive(Clone)]
-struct Authentication {
+struct TabbedEditor {
+    tags: Vec<&'static str>,
     state: EditorState,
     title: String,
     selected: bool,
     selected_tag: usize,
 }
 
-impl Widget for Authentication {
+impl Widget for TabbedEditor {
     fn render(mut self, area: Rect, buf: &mut Buffer) {
         let [title, content] = layout(area, Direction::Vertical, &[1, 0]);
 
-        let titles = vec![" Bearer ", " Basic "];
-        Tabs::new(titles)
+        Tabs::new(self.tags)
             .style(THEME.tabs)
             .highlight_style(THEME.tabs_selected)
             .select(self.selected_tag)
@@ -1,12 +1,13 @@
 #![allow(clippy::module_name_repetitions, clippy::cast_possible_truncation)]
 use crate::context::MessagesTab;
-use crate::model::MessagesModel;
+use crate::model::{messages::ResponseMetadata, MessagesModel};
 use crate::view::root::layout;
 use crate::widgets::{
     editor::{view_selected, view_unselected},
     tabs::ActivatableTabs,
 };
 use ratatui::prelude::*;
+use ratatui::widgets::{Block, BorderType, Borders, Paragraph, Wrap};
 
 use super::theme::THEME;
 
@@ -17,7 +18,17 @@ pub struct MessagesPage<'a> {
 }
 
 impl<'a> MessagesPage<'a> {
-    pub fn footer_keys() -> Vec<(&'static str, &'static str)> {
+    pub fn footer_keys(model: &MessagesModel) -> Vec<(&'static str, &'static str)> {
+        if model.is_session_open() {
+            return vec![
+                ("q", "Quit"),
+                ("↑", "Up"),
+                ("↓", "Down"),
+                ("Enter", "Send"),
+                ("^e", "End Stream"),
+                ("Esc", "Cancel"),
+            ];
+        }
         vec![
             ("q", "Quit"),
             ("Tab", "Next Tab"),
@@ -35,10 +46,14 @@ impl Widget for MessagesPage<'_> {
             Layout::vertical([Percentage(50), Length(1), Min(0)]).areas(area);
 
         // Request
+        let title = match self.model.request.raw {
+            Some(format) => format!("Request ({format})"),
+            None => String::from("Request"),
+        };
         let editor = if self.tab == MessagesTab::Request {
-            view_selected(&mut self.model.request.editor.state, "Request")
+            view_selected(&mut self.model.request.editor.state, title)
         } else {
-            view_unselected(&mut self.model.request.editor.state, "Request")
+            view_unselected(&mut self.model.request.editor.state, title)
         };
         editor.render(top, buf);
 
@@ -58,12 +73,80 @@ impl Widget for MessagesPage<'_> {
             tabs.render(right, buf);
         }
 
-        // Request
+        // Response
+        let metadata = &self.model.response.metadata;
+        let bottom = if metadata.is_empty() {
+            bottom
+        } else {
+            let [left, right] = Layout::horizontal([Percentage(65), Percentage(35)]).areas(bottom);
+            ResponseMetadataView { metadata }.render(right, buf);
+            left
+        };
         let editor = if self.tab == MessagesTab::Response {
             view_selected(&mut self.model.response.editor.state, "Response")
         } else {
             view_unselected(&mut self.model.response.editor.state, "Response")
         };
+        let bottom = if let Some(status) = metadata.metrics_line() {
+            let [editor_area, status_area] = Layout::vertical([Min(0), Length(1)]).areas(bottom);
+            Paragraph::new(Line::styled(format!(" {status}"), THEME.content))
+                .render(status_area, buf);
+            editor_area
+        } else {
+            bottom
+        };
         editor.render(bottom, buf);
     }
 }
+
+/// Lists the headers and trailers of the response.
+struct ResponseMetadataView<'a> {
+    metadata: &'a ResponseMetadata,
+}
+
+impl Widget for ResponseMetadataView<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let encoding = self.metadata.encoding.as_deref().unwrap_or("none");
+        let mut lines = vec![Line::from(vec![
+            Span::styled("Compression: ", THEME.content.add_modifier(Modifier::BOLD)),
+            Span::styled(encoding.to_string(), THEME.content),
+        ])];
+        if self.metadata.attempts > 0 {
+            lines.push(Line::from(vec![
+                Span::styled("Attempts: ", THEME.content.add_modifier(Modifier::BOLD)),
+                Span::styled(self.metadata.attempts.to_string(), THEME.content),
+            ]));
+        }
+        lines.push(Line::default());
+        for (title, pairs) in [
+            ("Headers", &self.metadata.headers),
+            ("Trailers", &self.metadata.trailers),
+        ] {
+            if pairs.is_empty() {
+                continue;
+            }
+            lines.push(Line::from(Span::styled(title, THEME.divider.title)));
+            for (key, val) in pairs {
+                lines.push(Line::from(vec![
+                    Span::styled(
+                        format!("{key}: "),
+                        THEME.content.add_modifier(Modifier::BOLD),
+                    ),
+                    Span::styled(val.clone(), THEME.content),
+                ]));
+            }
+            lines.push(Line::default());
+        }
+        let block = Block::new()
+            .borders(Borders::ALL)
+            .border_type(BorderType::Plain)
+            .title("Response Metadata")
+            .title_alignment(Alignment::Center)
+            .style(THEME.content)
+            .white();
+        Paragraph::new(lines)
+            .block(block)
+            .wrap(Wrap { trim: false })
+            .render(area, buf);
+    }
+}
@@ -48,7 +48,7 @@ impl Root<'_> {
     fn render_footer(&self, area: Rect, buf: &mut Buffer) {
         let keys = match self.ctx.tab {
             Tab::Selection => SelectionPage::footer_keys(self.ctx.selection_tab),
-            Tab::Messages => MessagesPage::footer_keys(),
+            Tab::Messages => MessagesPage::footer_keys(&self.ctx.messages.borrow()),
             Tab::Headers => HeadersPage::new(&self.ctx.headers.borrow()).footer_keys(),
         };
         let spans: Vec<Span> = keys
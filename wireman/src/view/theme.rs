@@ -15,6 +15,9 @@ pub struct Theme {
     pub list: List,
     pub status_line: (Style, Style),
     pub divider: Divider,
+    pub error: Style,
+    pub success: Style,
+    pub warning: Style,
 }
 
 pub struct KeyBinding {
@@ -70,6 +73,9 @@ pub const THEME: Theme = Theme {
         border_style: Style::new().fg(DARK_BLUE).bg(DARK_BLUE),
         border_type: BorderType::Thick,
     },
+    error: Style::new().fg(LIGHT_RED).bg(DARK_BLUE),
+    success: Style::new().fg(LIGHT_GREEN).bg(DARK_BLUE),
+    warning: Style::new().fg(LIGHT_AMBER).bg(DARK_BLUE),
 };
 
 // Blue
@@ -79,6 +85,15 @@ const DARK_BLUE: Color = tailwind::SLATE.c900;
 const LIGHT_PURPLE: Color = tailwind::PURPLE.c700;
 const DARK_PURPLE: Color = tailwind::PURPLE.c900;
 
+// Red
+const LIGHT_RED: Color = tailwind::RED.c400;
+
+// Green
+const LIGHT_GREEN: Color = tailwind::GREEN.c400;
+
+// Amber
+const LIGHT_AMBER: Color = tailwind::AMBER.c400;
+
 // Gray
 const LIGHT_GRAY: Color = tailwind::SLATE.c400;
 const MID_GRAY: Color = tailwind::SLATE.c600;
@@ -43,6 +43,48 @@ pub(super) const PURPLE: Palette = Palette {
     c950: from_u32(0x3b0764),
 };
 
+pub(super) const RED: Palette = Palette {
+    c50: from_u32(0xfef2f2),
+    c100: from_u32(0xfee2e2),
+    c200: from_u32(0xfecaca),
+    c300: from_u32(0xfca5a5),
+    c400: from_u32(0xf87171),
+    c500: from_u32(0xef4444),
+    c600: from_u32(0xdc2626),
+    c700: from_u32(0xb91c1c),
+    c800: from_u32(0x991b1b),
+    c900: from_u32(0x7f1d1d),
+    c950: from_u32(0x450a0a),
+};
+
+pub(super) const GREEN: Palette = Palette {
+    c50: from_u32(0xf0fdf4),
+    c100: from_u32(0xdcfce7),
+    c200: from_u32(0xbbf7d0),
+    c300: from_u32(0x86efac),
+    c400: from_u32(0x4ade80),
+    c500: from_u32(0x22c55e),
+    c600: from_u32(0x16a34a),
+    c700: from_u32(0x15803d),
+    c800: from_u32(0x166534),
+    c900: from_u32(0x14532d),
+    c950: from_u32(0x052e16),
+};
+
+pub(super) const AMBER: Palette = Palette {
+    c50: from_u32(0xfffbeb),
+    c100: from_u32(0xfef3c7),
+    c200: from_u32(0xfde68a),
+    c300: from_u32(0xfcd34d),
+    c400: from_u32(0xfbbf24),
+    c500: from_u32(0xf59e0b),
+    c600: from_u32(0xd97706),
+    c700: from_u32(0xb45309),
+    c800: from_u32(0x92400e),
+    c900: from_u32(0x78350f),
+    c950: from_u32(0x451a03),
+};
+
 const fn from_u32(u: u32) -> Color {
     let r = (u >> 16) as u8;
     let g = (u >> 8) as u8;
@@ -90,6 +90,14 @@ impl TextEditor {
         }
     }
 
+    /// Appends raw text to the end of the editors content
+    pub fn append_text_raw(&mut self, text: &str) {
+        for line in text.lines() {
+            let chars: Vec<char> = line.chars().collect();
+            self.state.lines.push(chars);
+        }
+    }
+
     /// Return the error
     pub fn get_error(&self) -> Option<ErrorKind> {
         self.error.clone()
@@ -217,23 +225,34 @@ pub fn yank_to_clipboard(text: &str) {
 pub struct ErrorKind {
     pub kind: String,
     pub msg: String,
+    /// The headers sent by the server with a failed call.
+    pub headers: Vec<(String, String)>,
+    /// The trailers sent by the server with a failed call.
+    pub trailers: Vec<(String, String)>,
 }
 
 unsafe impl Send for ErrorKind {}
 
 impl ErrorKind {
-    pub fn format_error(msg: String) -> Self {
+    fn new<T: Into<String>>(kind: &str, msg: T) -> Self {
         Self {
-            kind: "Format Error".to_owned(),
-            msg,
+            kind: kind.to_owned(),
+            msg: msg.into(),
+            headers: Vec::new(),
+            trailers: Vec::new(),
         }
     }
 
+    pub fn format_error(msg: String) -> Self {
+        Self::new("Format Error", msg)
+    }
+
     pub fn default_error<T: Into<String>>(msg: T) -> Self {
-        Self {
-            kind: "Error".to_string(),
-            msg: msg.into(),
-        }
+        Self::new("Error", msg)
+    }
+
+    pub fn deadline_exceeded(msg: String) -> Self {
+        Self::new("Deadline Exceeded", msg)
     }
 
     pub fn string(&self) -> String {
@@ -249,7 +268,24 @@ impl From<serde_json::Error> for ErrorKind {
 
 impl From<core::error::Error> for ErrorKind {
     fn from(err: core::error::Error) -> Self {
-        Self::default_error(err.to_string())
+        let status = match &err {
+            core::error::Error::GrpcError(status) => Some(status),
+            _ => None,
+        };
+        let mut kind = if err.is_deadline_exceeded() {
+            Self::deadline_exceeded(err.to_string())
+        } else if let Some(details) = status.and_then(|status| status.details.as_ref()) {
+            // Show the rich error details below the status
+            let details = serde_json::to_string_pretty(details).unwrap_or_default();
+            Self::default_error(format!("{err}\n\n{details}"))
+        } else {
+            Self::default_error(err.to_string())
+        };
+        if let Some(status) = status {
+            kind.headers = status.headers.to_pairs();
+            kind.trailers = status.trailers.to_pairs();
+        }
+        kind
     }
 }
 
@@ -262,7 +298,7 @@ pub fn pretty_format_json(input: &str) -> Result<String, ErrorKind> {
 }
 
 /// Returns the editors view when selected.
-pub fn view_selected<S: Into<String>>(state: &mut EditorState, title: S) -> EditorView {
+pub fn view_selected<S: Into<String>>(state: &mut EditorState, title: S) -> EditorView<'_, '_> {
     let block = Block::new()
         .borders(Borders::ALL)
         .border_type(BorderType::Double)
@@ -279,7 +315,7 @@ pub fn view_selected<S: Into<String>>(state: &mut EditorState, title: S) -> Edit
 }
 
 /// Returns the editors view when unselected
-pub fn view_unselected<S: Into<String>>(state: &mut EditorState, title: S) -> EditorView {
+pub fn view_unselected<S: Into<String>>(state: &mut EditorState, title: S) -> EditorView<'_, '_> {
     let block = Block::new()
         .borders(Borders::ALL)
         .border_type(BorderType::Plain)
@@ -295,7 +331,10 @@ pub fn view_unselected<S: Into<String>>(state: &mut EditorState, title: S) -> Ed
 }
 
 /// Returns the editors view for a single line editor when selected.
-pub fn view_single_selected<S: Into<String>>(state: &mut EditorState, title: S) -> EditorView {
+pub fn view_single_selected<S: Into<String>>(
+    state: &mut EditorState,
+    title: S,
+) -> EditorView<'_, '_> {
     let block = Block::new()
         .borders(Borders::ALL)
         .border_type(BorderType::Double)
@@ -308,7 +347,10 @@ pub fn view_single_selected<S: Into<String>>(state: &mut EditorState, title: S)
 }
 
 /// Returns the editors view for a single line editor when unselected.
-pub fn view_single_unselected<S: Into<String>>(state: &mut EditorState, title: S) -> EditorView {
+pub fn view_single_unselected<S: Into<String>>(
+    state: &mut EditorState,
+    title: S,
+) -> EditorView<'_, '_> {
     let block = Block::new()
         .borders(Borders::ALL)
         .border_type(BorderType::Plain)
@@ -322,3 +364,26 @@ pub fn view_single_unselected<S: Into<String>>(state: &mut EditorState, title: S
         .hide_cursor();
     EditorView::new(state).theme(theme)
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use core::error::GrpcStatus;
+
+    #[test]
+    fn test_error_kind_from_status_details() {
+        // given
+        let err = core::error::Error::from(GrpcStatus {
+            details: Some(serde_json::json!({"code": 3})),
+            ..GrpcStatus::new(core::error::Code::InvalidArgument, "invalid number")
+        });
+
+        // when
+        let kind = ErrorKind::from(err);
+
+        // then
+        let expected =
+            "grpc: status: InvalidArgument, message: \"invalid number\"\n\n{\n  \"code\": 3\n}";
+        assert_eq!(kind.msg, expected);
+    }
+}